@@ -0,0 +1,278 @@
+//! Benchmarks measuring the instructions per second executed by `Chip::tick`, for synthetic
+//! instruction mixes and for the programs in the `games` folder.
+//!
+//! Run them with `cargo bench --bench tick`. Criterion reports the throughput in elements per
+//! second, where every element is an executed instruction, and compares every run with the
+//! previous one to catch regressions. The games run without the display wait quirk, so no tick
+//! is a stall waiting for the next frame, and their reloads after a halt are not timed.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use chip8vm::chip::Chip;
+use chip8vm::database;
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::quirks::Quirks;
+use chip8vm::{Keypad, Random, Screen};
+
+/// Number of instructions executed per frame.
+const TICKS_X_FRAME: u64 = 10;
+
+/// Frames executed by every benchmark iteration.
+const FRAMES: u64 = 60;
+
+/// Frames a key is pressed, and released, by the keypad of the games.
+const PRESS_FRAMES: u64 = 5;
+
+/// Arithmetic and logic instructions, with the random number generator.
+const ALU_MIX: &[u8] = &[
+    0x60, 0x01, // LD V0, 1
+    0x61, 0x02, // LD V1, 2
+    0x80, 0x14, // ADD V0, V1
+    0x80, 0x15, // SUB V0, V1
+    0x80, 0x17, // SUBN V0, V1
+    0x80, 0x12, // AND V0, V1
+    0x80, 0x11, // OR V0, V1
+    0x80, 0x13, // XOR V0, V1
+    0x80, 0x16, // SHR V0, V1
+    0x80, 0x1E, // SHL V0, V1
+    0x70, 0x05, // ADD V0, 5
+    0x81, 0x00, // LD V1, V0
+    0xC2, 0xFF, // RND V2, 0xFF
+    0x12, 0x00, // JP 0x200
+];
+
+/// Conditional skips, jumps, calls and returns.
+const BRANCH_MIX: &[u8] = &[
+    0x60, 0x00, // LD V0, 0
+    0x61, 0x00, // LD V1, 0
+    0x30, 0x00, // SE V0, 0
+    0x12, 0x00, // JP 0x200
+    0x40, 0x01, // SNE V0, 1
+    0x12, 0x00, // JP 0x200
+    0x50, 0x10, // SE V0, V1
+    0x12, 0x00, // JP 0x200
+    0x22, 0x16, // CALL 0x216
+    0x12, 0x00, // JP 0x200
+    0x00, 0x00, // Unused
+    0x00, 0xEE, // RET
+];
+
+/// Index register, memory, font and timer instructions.
+const MEMORY_MIX: &[u8] = &[
+    0xA3, 0x00, // LD I, 0x300
+    0x60, 0x7B, // LD V0, 123
+    0xF0, 0x33, // LD B, V0
+    0xF2, 0x65, // LD V2, [I]
+    0xF2, 0x55, // LD [I], V2
+    0xF0, 0x1E, // ADD I, V0
+    0xF0, 0x29, // LD F, V0
+    0xF0, 0x15, // LD DT, V0
+    0xF0, 0x07, // LD V0, DT
+    0xF0, 0x18, // LD ST, V0
+    0x12, 0x00, // JP 0x200
+];
+
+/// Full height sprites covering the screen, with a clear every screen.
+const DRAW_MIX: &[u8] = &[
+    0x00, 0xE0, // CLS
+    0x60, 0x00, // LD V0, 0
+    0x61, 0x00, // LD V1, 0
+    0xA2, 0x1A, // LD I, 0x21A
+    0xD0, 0x1F, // DRW V0, V1, 15
+    0x70, 0x08, // ADD V0, 8
+    0x30, 0x40, // SE V0, 64
+    0x12, 0x08, // JP 0x208
+    0x60, 0x00, // LD V0, 0
+    0x71, 0x0F, // ADD V1, 15
+    0x31, 0x2D, // SE V1, 45
+    0x12, 0x08, // JP 0x208
+    0x12, 0x00, // JP 0x200
+    0xFF, 0x81, 0xBD, 0xA5, 0xA5, 0xBD, 0x81, 0xFF, 0x81, 0xBD, 0xA5, 0xA5, 0xBD, 0x81, 0xFF,
+];
+
+/// Screen discarding the pixels, so only the interpreter is measured.
+struct NullScreen;
+
+impl Screen for NullScreen {
+    fn clear(&mut self) {}
+
+    fn draw(&mut self, _x: u8, _y: u8) -> bool {
+        false
+    }
+}
+
+/// Xorshift generator, so every run executes the same instructions.
+struct BenchRandom(u32);
+
+impl Random for BenchRandom {
+    fn range(&mut self) -> u8 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as u8
+    }
+}
+
+/// Keypad pressing every key in turn, to get the games past their key waits.
+struct BenchKeypad {
+    key: Option<u8>,
+}
+
+impl Keypad for BenchKeypad {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.key == Some(keycode)
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        self.key
+    }
+}
+
+/// Creates a chip with the program loaded.
+///
+/// # Parameters
+/// * rom - Program to load.
+/// * quirks - Quirks of the interpreter.
+fn load(rom: &[u8], quirks: Quirks) -> Chip {
+    let mut chip = Chip::default();
+    chip.set_quirks(quirks);
+    assert!(chip.load_rom(rom), "Program too large.");
+    chip
+}
+
+/// Running program with its dependencies.
+struct Machine {
+    rom: Vec<u8>,
+    quirks: Quirks,
+    chip: Chip,
+    random: BenchRandom,
+    keypad: BenchKeypad,
+    frame: u64,
+}
+
+impl Machine {
+    /// Creates a machine running the program with the quirks.
+    ///
+    /// # Parameters
+    /// * rom - Program to run.
+    /// * quirks - Quirks of the interpreter.
+    fn new(rom: &[u8], quirks: Quirks) -> Machine {
+        Machine {
+            rom: rom.to_vec(),
+            quirks,
+            chip: load(rom, quirks),
+            random: BenchRandom(0x2545_F491),
+            keypad: BenchKeypad { key: None },
+            frame: 0,
+        }
+    }
+
+    /// Executes FRAMES frames of TICKS_X_FRAME instructions, pressing a key during PRESS_FRAMES
+    /// frames followed by PRESS_FRAMES frames without keys. Programs stopping, like games leaking
+    /// stack frames after a while, are restarted without timing the restart.
+    ///
+    /// # Parameters
+    /// * screen - Screen receiving the drawn pixels.
+    ///
+    /// # Return
+    /// Time spent executing the instructions and the timers.
+    fn run(&mut self, screen: &mut impl Screen) -> Duration {
+        let mut elapsed = Duration::ZERO;
+        let mut start = Instant::now();
+
+        for _frame in 0..FRAMES {
+            let step = self.frame / PRESS_FRAMES;
+            self.keypad.key = if step % 2 == 0 { Some((step / 2 % 16) as u8) } else { None };
+            self.frame += 1;
+
+            let mut ticks = 0;
+            while ticks < TICKS_X_FRAME {
+                if self.chip.tick(&mut self.random, screen, &self.keypad) {
+                    ticks += 1;
+                } else {
+                    elapsed += start.elapsed();
+                    self.chip = load(&self.rom, self.quirks);
+                    start = Instant::now();
+                }
+            }
+            self.chip.tick_timers();
+        }
+
+        elapsed + start.elapsed()
+    }
+
+    /// Runs the machine for the iterations of a benchmark.
+    ///
+    /// # Parameters
+    /// * iterations - Number of iterations to run.
+    /// * screen - Screen receiving the drawn pixels.
+    ///
+    /// # Return
+    /// Time spent in all the iterations.
+    fn iterate(&mut self, iterations: u64, screen: &mut impl Screen) -> Duration {
+        (0..iterations).map(|_| self.run(screen)).sum()
+    }
+}
+
+fn instruction_mixes(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("mixes");
+    group.throughput(Throughput::Elements(FRAMES * TICKS_X_FRAME));
+
+    for (name, program) in [("alu", ALU_MIX), ("branch", BRANCH_MIX), ("memory", MEMORY_MIX)] {
+        let mut machine = Machine::new(program, Quirks::CHIP8);
+        group.bench_function(name, |bencher| {
+            bencher.iter_custom(|iterations| machine.iterate(iterations, &mut NullScreen))
+        });
+    }
+    group.finish();
+}
+
+fn draw_screens(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("draw");
+    group.throughput(Throughput::Elements(FRAMES * TICKS_X_FRAME));
+
+    let mut machine = Machine::new(DRAW_MIX, Quirks::CHIP8);
+    group.bench_function("null", |bencher| {
+        bencher.iter_custom(|iterations| machine.iterate(iterations, &mut NullScreen))
+    });
+
+    let mut machine = Machine::new(DRAW_MIX, Quirks::CHIP8);
+    let mut screen = FrameBuffer::default();
+    group.bench_function("framebuffer", |bencher| {
+        bencher.iter_custom(|iterations| machine.iterate(iterations, &mut screen))
+    });
+    group.finish();
+}
+
+fn games(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("games");
+    group.throughput(Throughput::Elements(FRAMES * TICKS_X_FRAME));
+
+    let mut paths: Vec<_> = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("games"))
+        .expect("Unable to read the games folder.")
+        .map(|entry| entry.expect("Unable to read the games folder.").path())
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let rom = fs::read(&path).expect("Unable to read game.");
+        let quirks = Quirks {
+            display_wait: false,
+            ..database::lookup(&rom).map_or(Quirks::CHIP8, |info| info.quirks)
+        };
+        let mut machine = Machine::new(&rom, quirks);
+
+        let name = path.file_name().unwrap().to_string_lossy();
+        group.bench_function(name.as_ref(), |bencher| {
+            bencher.iter_custom(|iterations| machine.iterate(iterations, &mut NullScreen))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, instruction_mixes, draw_screens, games);
+criterion_main!(benches);
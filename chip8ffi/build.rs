@@ -0,0 +1,35 @@
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let header = out_dir.join("chip8vm.h");
+
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-changed=tests/c/test_chip8vm.c");
+
+    // Generate the C header from the exported API. The c_api test checks that the checked-in
+    // include/chip8vm.h matches it.
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Unable to generate C header.")
+        .write_to_file(&header);
+
+    // Build the C test program only for native builds, where the tests can run, and link it
+    // only into the tests, so it never ends up in the library.
+    if env::var("TARGET").unwrap() != env::var("HOST").unwrap() {
+        return;
+    }
+    cc::Build::new()
+        .file(crate_dir.join("tests").join("c").join("test_chip8vm.c"))
+        .include(&out_dir)
+        .warnings(true)
+        .cargo_metadata(false)
+        .compile("chip8vm_ctest");
+    println!("cargo:rustc-link-arg-tests={}", out_dir.join("libchip8vm_ctest.a").display());
+}
@@ -0,0 +1,115 @@
+#ifndef CHIP8VM_H
+#define CHIP8VM_H
+
+/* This file is generated by cbindgen from chip8ffi/src/lib.rs. Do not edit it manually. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Size in bytes of the storage needed by a Chip8Vm instance.
+ */
+#define CHIP8_VM_STORAGE_SIZE 4608
+
+/**
+ * Alignment in bytes of the storage needed by a Chip8Vm instance.
+ */
+#define CHIP8_VM_STORAGE_ALIGN 8
+
+/**
+ * Chip8 VM instance with its host callbacks. Opaque for C hosts.
+ */
+typedef struct Chip8Vm Chip8Vm;
+
+/**
+ * Callbacks used by the VM to access the host screen, keypad and random number generator.
+ *
+ * The user_data pointer is passed back to every callback. Missing callbacks behave as a
+ * screen without collisions, a keypad without pressed keys and a random generator returning
+ * always 0.
+ */
+typedef struct Chip8Callbacks {
+  /**
+   * Host data passed to every callback.
+   */
+  void *user_data;
+  /**
+   * Clears the screen.
+   */
+  void (*clear)(void *user_data);
+  /**
+   * Xors the pixel in the (x, y) coordinates, returning true on collision.
+   */
+  bool (*draw)(void *user_data, uint8_t x, uint8_t y);
+  /**
+   * Returns true if the key is pressed. From 0x0 to 0xF.
+   */
+  bool (*is_pressed)(void *user_data, uint8_t keycode);
+  /**
+   * Returns the pressed key, from 0x0 to 0xF, or a negative value if no key is pressed.
+   */
+  int8_t (*pressed_key)(void *user_data);
+  /**
+   * Returns a random number between 0 and 255.
+   */
+  uint8_t (*random)(void *user_data);
+} Chip8Callbacks;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Initializes a VM instance in the storage provided by the host.
+ *
+ * Returns NULL if the storage is NULL, smaller than CHIP8_VM_STORAGE_SIZE or not aligned to
+ * CHIP8_VM_STORAGE_ALIGN.
+ *
+ * # Safety
+ * The storage must be valid for writes of `size` bytes and must outlive the returned VM.
+ */
+struct Chip8Vm *chip8_vm_init(void *storage, size_t size, struct Chip8Callbacks callbacks);
+
+/**
+ * Replaces the callbacks of the VM.
+ *
+ * # Safety
+ * The vm must be a pointer returned by chip8_vm_init.
+ */
+void chip8_vm_set_callbacks(struct Chip8Vm *vm, struct Chip8Callbacks callbacks);
+
+/**
+ * Loads a ROM in the VM memory, together with the font.
+ *
+ * Returns false if the ROM does not fit in the program memory.
+ *
+ * # Safety
+ * The vm must be a pointer returned by chip8_vm_init and the rom must be valid for reads of
+ * `length` bytes.
+ */
+bool chip8_vm_load_rom(struct Chip8Vm *vm, const uint8_t *rom, size_t length);
+
+/**
+ * Decodes and executes the current instruction. Should be called at around 600Hz.
+ *
+ * Returns false if the instruction could not be executed.
+ *
+ * # Safety
+ * The vm must be a pointer returned by chip8_vm_init.
+ */
+bool chip8_vm_step(struct Chip8Vm *vm);
+
+/**
+ * Decreases the VM timers. Must be called at 60Hz.
+ *
+ * # Safety
+ * The vm must be a pointer returned by chip8_vm_init.
+ */
+void chip8_vm_tick_timers(struct Chip8Vm *vm);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHIP8VM_H */
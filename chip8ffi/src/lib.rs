@@ -0,0 +1,193 @@
+#![no_std]
+//! The chip8vm-ffi crate exposes the chip8vm::chip::Chip VM through a stable C API, so it can
+//! be hosted by C programs and firmware. The generated header is `include/chip8vm.h`.
+//!
+//! The VM does not allocate: the host provides the storage for every instance, usually a
+//! static buffer of CHIP8_VM_STORAGE_SIZE bytes aligned to CHIP8_VM_STORAGE_ALIGN, and the
+//! screen, keypad and random dependencies are provided as callbacks.
+//!
+//! Firmware without a panic handler of its own can enable the `panic-handler` feature, which
+//! defines one looping forever on bare metal targets.
+
+#[cfg(not(target_os = "none"))]
+extern crate std;
+
+use core::ffi::c_void;
+use core::mem::{align_of, size_of, MaybeUninit};
+use core::ptr;
+use core::slice;
+
+use chip8vm::chip::Chip;
+use chip8vm::PROGRAM_SIZE;
+
+/// Size in bytes of the storage needed by a Chip8Vm instance.
+pub const CHIP8_VM_STORAGE_SIZE: usize = 4608;
+
+/// Alignment in bytes of the storage needed by a Chip8Vm instance.
+pub const CHIP8_VM_STORAGE_ALIGN: usize = 8;
+
+const _: () = assert!(size_of::<Chip8Vm>() <= CHIP8_VM_STORAGE_SIZE);
+const _: () = assert!(align_of::<Chip8Vm>() <= CHIP8_VM_STORAGE_ALIGN);
+
+/// Callbacks used by the VM to access the host screen, keypad and random number generator.
+///
+/// The user_data pointer is passed back to every callback. Missing callbacks behave as a
+/// screen without collisions, a keypad without pressed keys and a random generator returning
+/// always 0.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct Chip8Callbacks {
+    /// Host data passed to every callback.
+    pub user_data: *mut c_void,
+
+    /// Clears the screen.
+    pub clear: Option<extern "C" fn(user_data: *mut c_void)>,
+
+    /// Xors the pixel in the (x, y) coordinates, returning true on collision.
+    pub draw: Option<extern "C" fn(user_data: *mut c_void, x: u8, y: u8) -> bool>,
+
+    /// Returns true if the key is pressed. From 0x0 to 0xF.
+    pub is_pressed: Option<extern "C" fn(user_data: *mut c_void, keycode: u8) -> bool>,
+
+    /// Returns the pressed key, from 0x0 to 0xF, or a negative value if no key is pressed.
+    pub pressed_key: Option<extern "C" fn(user_data: *mut c_void) -> i8>,
+
+    /// Returns a random number between 0 and 255.
+    pub random: Option<extern "C" fn(user_data: *mut c_void) -> u8>,
+}
+
+/// Chip8 VM instance with its host callbacks. Opaque for C hosts.
+pub struct Chip8Vm {
+    chip: Chip,
+    callbacks: Chip8Callbacks,
+}
+
+/// Adapter implementing the chip8vm traits over the host callbacks.
+struct Host(Chip8Callbacks);
+
+impl chip8vm::Random for Host {
+    fn range(&mut self) -> u8 {
+        self.0.random.map_or(0, |random| random(self.0.user_data))
+    }
+}
+
+impl chip8vm::Screen for Host {
+    fn clear(&mut self) {
+        if let Some(clear) = self.0.clear {
+            clear(self.0.user_data);
+        }
+    }
+
+    fn draw(&mut self, x: u8, y: u8) -> bool {
+        self.0.draw.is_some_and(|draw| draw(self.0.user_data, x, y))
+    }
+}
+
+impl chip8vm::Keypad for Host {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.0.is_pressed.is_some_and(|is_pressed| is_pressed(self.0.user_data, keycode))
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        let key = self.0.pressed_key.map_or(-1, |pressed_key| pressed_key(self.0.user_data));
+        if key < 0 {
+            None
+        } else {
+            Some(key as u8)
+        }
+    }
+}
+
+/// Initializes a VM instance in the storage provided by the host.
+///
+/// Returns NULL if the storage is NULL, smaller than CHIP8_VM_STORAGE_SIZE or not aligned to
+/// CHIP8_VM_STORAGE_ALIGN.
+///
+/// # Safety
+/// The storage must be valid for writes of `size` bytes and must outlive the returned VM.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_vm_init(
+    storage: *mut c_void,
+    size: usize,
+    callbacks: Chip8Callbacks,
+) -> *mut Chip8Vm {
+    if storage.is_null()
+        || size < CHIP8_VM_STORAGE_SIZE
+        || (storage as usize) % CHIP8_VM_STORAGE_ALIGN != 0
+    {
+        return ptr::null_mut();
+    }
+
+    // Initialize the VM in place, so the chip is never constructed on the host stack.
+    let vm = storage as *mut Chip8Vm;
+    Chip::init(&mut *(ptr::addr_of_mut!((*vm).chip) as *mut MaybeUninit<Chip>));
+    ptr::addr_of_mut!((*vm).callbacks).write(callbacks);
+    vm
+}
+
+/// Replaces the callbacks of the VM.
+///
+/// # Safety
+/// The vm must be a pointer returned by chip8_vm_init.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_vm_set_callbacks(vm: *mut Chip8Vm, callbacks: Chip8Callbacks) {
+    if let Some(vm) = vm.as_mut() {
+        vm.callbacks = callbacks;
+    }
+}
+
+/// Loads a ROM in the VM memory, together with the font.
+///
+/// Returns false if the ROM does not fit in the program memory.
+///
+/// # Safety
+/// The vm must be a pointer returned by chip8_vm_init and the rom must be valid for reads of
+/// `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_vm_load_rom(vm: *mut Chip8Vm, rom: *const u8, length: usize) -> bool {
+    let vm = match vm.as_mut() {
+        Some(vm) => vm,
+        None => return false,
+    };
+    if rom.is_null() || length > PROGRAM_SIZE {
+        return false;
+    }
+
+    vm.chip.load_rom(slice::from_raw_parts(rom, length))
+}
+
+/// Decodes and executes the current instruction. Should be called at around 600Hz.
+///
+/// Returns false if the instruction could not be executed.
+///
+/// # Safety
+/// The vm must be a pointer returned by chip8_vm_init.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_vm_step(vm: *mut Chip8Vm) -> bool {
+    let vm = match vm.as_mut() {
+        Some(vm) => vm,
+        None => return false,
+    };
+
+    let mut random = Host(vm.callbacks);
+    let mut screen = Host(vm.callbacks);
+    let keypad = Host(vm.callbacks);
+    vm.chip.tick(&mut random, &mut screen, &keypad)
+}
+
+/// Decreases the VM timers. Must be called at 60Hz.
+///
+/// # Safety
+/// The vm must be a pointer returned by chip8_vm_init.
+#[no_mangle]
+pub unsafe extern "C" fn chip8_vm_tick_timers(vm: *mut Chip8Vm) {
+    if let Some(vm) = vm.as_mut() {
+        vm.chip.tick_timers();
+    }
+}
+
+#[cfg(all(feature = "panic-handler", target_os = "none"))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
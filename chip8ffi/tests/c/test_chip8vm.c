@@ -0,0 +1,109 @@
+/*
+ * C test program for the chip8vm C API. Built by build.rs for native builds, linked only into
+ * the tests and run by the c_api integration test through chip8vm_c_test, which returns the
+ * number of failed checks.
+ */
+#include "chip8vm.h"
+
+#define SCREEN_WIDTH 64
+#define SCREEN_HEIGHT 32
+
+typedef struct Host {
+    uint8_t pixels[SCREEN_WIDTH * SCREEN_HEIGHT];
+    int clears;
+    int pressed;
+} Host;
+
+static _Alignas(CHIP8_VM_STORAGE_ALIGN) uint8_t storage[CHIP8_VM_STORAGE_SIZE];
+
+static void host_clear(void *user_data) {
+    Host *host = (Host *)user_data;
+    for (int i = 0; i < SCREEN_WIDTH * SCREEN_HEIGHT; i++) {
+        host->pixels[i] = 0;
+    }
+    host->clears++;
+}
+
+static bool host_draw(void *user_data, uint8_t x, uint8_t y) {
+    Host *host = (Host *)user_data;
+    uint8_t *pixel = &host->pixels[y * SCREEN_WIDTH + x];
+    bool collision = *pixel != 0;
+    *pixel ^= 1;
+    return collision;
+}
+
+static bool host_is_pressed(void *user_data, uint8_t keycode) {
+    Host *host = (Host *)user_data;
+    return host->pressed == keycode;
+}
+
+static int8_t host_pressed_key(void *user_data) {
+    Host *host = (Host *)user_data;
+    return (int8_t)host->pressed;
+}
+
+static uint8_t host_random(void *user_data) {
+    (void)user_data;
+    return 0x5A;
+}
+
+int chip8vm_c_test(void) {
+    int failures = 0;
+    Host host = { .clears = 0, .pressed = -1 };
+    Chip8Callbacks callbacks = {
+        .user_data = &host,
+        .clear = host_clear,
+        .draw = host_draw,
+        .is_pressed = host_is_pressed,
+        .pressed_key = host_pressed_key,
+        .random = host_random,
+    };
+
+    /* Storage checks. */
+    uint8_t small[16];
+    failures += chip8_vm_init(small, sizeof(small), callbacks) != NULL;
+    failures += chip8_vm_init(storage, sizeof(storage) - 1, callbacks) != NULL;
+    failures += chip8_vm_init(storage + 1, sizeof(storage) - 1, callbacks) != NULL;
+
+    Chip8Vm *vm = chip8_vm_init(storage, sizeof(storage), callbacks);
+    failures += vm == NULL;
+    if (vm == NULL) {
+        return failures;
+    }
+
+    const uint8_t rom[] = {
+        0x00, 0xE0, /* CLS */
+        0xC0, 0xFF, /* RND V0, 0xFF */
+        0xF1, 0x0A, /* LD V1, K */
+        0xF1, 0x29, /* LD F, V1 */
+        0xD2, 0x25, /* DRW V2, V2, 5 */
+        0x00, 0x00,
+    };
+    failures += !chip8_vm_load_rom(vm, rom, sizeof(rom));
+
+    failures += !chip8_vm_step(vm);
+    failures += host.clears != 1;
+    failures += !chip8_vm_step(vm);
+
+    /* Waits for a key while none is pressed. */
+    failures += !chip8_vm_step(vm);
+    failures += !chip8_vm_step(vm);
+    host.pressed = 0x7;
+    failures += !chip8_vm_step(vm);
+
+    failures += !chip8_vm_step(vm);
+    failures += !chip8_vm_step(vm);
+
+    /* Sprite of the character 7: 0xF0, 0x10, 0x20, 0x40, 0x40. */
+    failures += host.pixels[0] != 1 || host.pixels[3] != 1 || host.pixels[4] != 0;
+    failures += host.pixels[SCREEN_WIDTH + 3] != 1;
+    failures += host.pixels[4 * SCREEN_WIDTH + 1] != 1;
+
+    /* The end of the program stops the VM. */
+    failures += chip8_vm_step(vm);
+
+    chip8_vm_tick_timers(vm);
+    failures += chip8_vm_load_rom(vm, rom, CHIP8_VM_STORAGE_SIZE);
+
+    return failures;
+}
@@ -0,0 +1,22 @@
+extern "C" {
+    // Entry point of the C test program in tests/c, returning the number of failed checks.
+    fn chip8vm_c_test() -> i32;
+}
+
+// Links the crate with the C API symbols used by the C test program.
+extern crate chip8vm_ffi;
+
+#[test]
+fn c_test_program() {
+    let failures = unsafe { chip8vm_c_test() };
+
+    assert_eq!(failures, 0);
+}
+
+#[test]
+fn header_up_to_date() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/chip8vm.h"));
+    let checked_in = include_str!("../include/chip8vm.h");
+
+    assert!(generated == checked_in, "include/chip8vm.h is outdated, regenerate it with cbindgen.");
+}
@@ -0,0 +1,194 @@
+//! The chip8vm-python crate provides the `chip8vm` Python module, exposing Chip-8 programs as
+//! small reinforcement learning environments.
+//!
+//! ```python
+//! import numpy
+//! from chip8vm import Chip8Env
+//!
+//! env = Chip8Env(open("games/BRIX", "rb").read(), frame_skip=4, seed=1)
+//! env.set_reward_fn(lambda memory: float(memory[0xF00]))
+//! observation = numpy.asarray(env.reset())  # uint8 array with shape (32, 64)
+//! observation, reward, terminated, truncated, info = env.step(0x4)
+//! ```
+
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use pyo3::exceptions::{PyBufferError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use chip8vm::env::{Env, EnvConfig, Never, NoReward, NO_ACTION};
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::{KEYPAD_NUM_KEYS, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Observation of the screen, one byte per pixel (0 or 1), exported through the buffer
+/// protocol as a read-only uint8 array with shape (SCREEN_HEIGHT, SCREEN_WIDTH).
+#[pyclass(module = "chip8vm", frozen)]
+pub struct Frame {
+    pixels: [u8; SCREEN_WIDTH * SCREEN_HEIGHT],
+    shape: [ffi::Py_ssize_t; 2],
+    strides: [ffi::Py_ssize_t; 2],
+}
+
+impl Frame {
+    fn new(framebuffer: &FrameBuffer) -> Frame {
+        let mut pixels = [0; SCREEN_WIDTH * SCREEN_HEIGHT];
+        for (position, pixel) in pixels.iter_mut().enumerate() {
+            *pixel = framebuffer.is_on(position % SCREEN_WIDTH, position / SCREEN_WIDTH) as u8;
+        }
+
+        Frame {
+            pixels,
+            shape: [SCREEN_HEIGHT as ffi::Py_ssize_t, SCREEN_WIDTH as ffi::Py_ssize_t],
+            strides: [SCREEN_WIDTH as ffi::Py_ssize_t, 1],
+        }
+    }
+}
+
+#[pymethods]
+impl Frame {
+    /// Returns the pixels as bytes, row by row.
+    fn tobytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.pixels)
+    }
+
+    unsafe fn __getbuffer__(slf: Bound<'_, Self>, view: *mut ffi::Py_buffer, flags: c_int) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Frame is not writable"));
+        }
+
+        let frame = slf.get();
+        (*view).buf = frame.pixels.as_ptr() as *mut c_void;
+        (*view).len = frame.pixels.len() as ffi::Py_ssize_t;
+        (*view).readonly = 1;
+        (*view).itemsize = 1;
+        (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+            b"B\0".as_ptr() as *mut c_char
+        } else {
+            ptr::null_mut()
+        };
+        (*view).ndim = 2;
+        (*view).shape = frame.shape.as_ptr() as *mut ffi::Py_ssize_t;
+        (*view).strides = frame.strides.as_ptr() as *mut ffi::Py_ssize_t;
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        (*view).obj = slf.into_any().into_ptr();
+        Ok(())
+    }
+}
+
+/// Chip-8 program wrapped as an environment with a gym-like API, running on chip8vm::env::Env.
+///
+/// Actions are the keycode pressed during the step, from 0x0 to 0xF, or None for no key. Every
+/// step runs frame_skip frames of ticks_per_frame instructions each, decreasing the timers once
+/// per frame. Rewards and episode termination are computed by optional Python functions
+/// receiving the VM memory as bytes, and episodes are truncated after max_frames frames, if it
+/// is not zero.
+#[pyclass(module = "chip8vm")]
+pub struct Chip8Env {
+    env: Env<Vec<u8>, NoReward, Never>,
+    reward_fn: Option<Py<PyAny>>,
+    done_fn: Option<Py<PyAny>>,
+}
+
+#[pymethods]
+impl Chip8Env {
+    #[new]
+    #[pyo3(signature = (rom, frame_skip = 4, ticks_per_frame = 10, seed = 0, max_frames = 0))]
+    fn new(rom: &[u8], frame_skip: u32, ticks_per_frame: u32, seed: u64, max_frames: u64) -> PyResult<Self> {
+        let config = EnvConfig {
+            frame_skip,
+            ticks_per_frame,
+            max_frames,
+        };
+        let env = Env::new(rom.to_vec(), seed, config, NoReward, Never)
+            .ok_or_else(|| PyValueError::new_err("The ROM does not fit in the program memory."))?;
+
+        Ok(Chip8Env {
+            env,
+            reward_fn: None,
+            done_fn: None,
+        })
+    }
+
+    /// Number of available actions, one per key.
+    #[classattr]
+    const ACTIONS: usize = KEYPAD_NUM_KEYS;
+
+    /// Restarts the program, optionally changing the random seed, and returns the first
+    /// observation.
+    #[pyo3(signature = (seed = None))]
+    fn reset(&mut self, seed: Option<u64>) -> Frame {
+        match seed {
+            Some(seed) => Frame::new(self.env.reset_with_seed(seed)),
+            None => Frame::new(self.env.reset()),
+        }
+    }
+
+    /// Runs a step with the provided action, returning the (observation, reward, terminated,
+    /// truncated, info) tuple. The episode terminates when the program stops or the done
+    /// function returns true, and it is truncated at max_frames. The info dictionary holds the
+    /// number of frames of the episode.
+    #[pyo3(signature = (action = None))]
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        action: Option<u8>,
+    ) -> PyResult<(Frame, f64, bool, bool, Bound<'py, PyDict>)> {
+        if action.is_some_and(|key| key as usize >= KEYPAD_NUM_KEYS) {
+            return Err(PyValueError::new_err("Actions must be keycodes from 0x0 to 0xF."));
+        }
+
+        let step = self.env.step(action.map_or(NO_ACTION, |key| key as usize));
+        let observation = Frame::new(step.observation);
+        let halted = step.terminated;
+        let truncated = step.truncated;
+
+        let memory = PyBytes::new(py, self.env.memory());
+        let reward = match &self.reward_fn {
+            Some(reward_fn) => reward_fn.call1(py, (&memory,))?.extract(py)?,
+            None => 0.0,
+        };
+        let terminated = halted
+            || match &self.done_fn {
+                Some(done_fn) => done_fn.call1(py, (&memory,))?.extract(py)?,
+                None => false,
+            };
+
+        let info = PyDict::new(py);
+        info.set_item("frames", self.env.frames())?;
+        Ok((observation, reward, terminated, truncated, info))
+    }
+
+    /// Sets the function computing the reward of every step from the VM memory.
+    #[pyo3(signature = (reward_fn = None))]
+    fn set_reward_fn(&mut self, reward_fn: Option<Py<PyAny>>) {
+        self.reward_fn = reward_fn;
+    }
+
+    /// Sets the function deciding from the VM memory if the episode is terminated.
+    #[pyo3(signature = (done_fn = None))]
+    fn set_done_fn(&mut self, done_fn: Option<Py<PyAny>>) {
+        self.done_fn = done_fn;
+    }
+
+    /// Returns a copy of the VM memory.
+    #[getter]
+    fn memory<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.env.memory())
+    }
+}
+
+/// Python module definition.
+#[pymodule]
+#[pyo3(name = "chip8vm")]
+fn chip8vm_python(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Chip8Env>()?;
+    module.add_class::<Frame>()?;
+    Ok(())
+}
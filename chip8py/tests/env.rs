@@ -0,0 +1,160 @@
+use std::ffi::CString;
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyModule};
+
+use chip8vm_python::{Chip8Env, Frame};
+
+// Runs the Python code with a chip8vm module providing the environment classes.
+fn run_python(code: &str) {
+    Python::attach(|py| {
+        let module = PyModule::new(py, "chip8vm").unwrap();
+        module.add_class::<Chip8Env>().unwrap();
+        module.add_class::<Frame>().unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("chip8vm", module).unwrap();
+
+        let code = CString::new(code).unwrap();
+        if let Err(error) = py.run(&code, Some(&globals), None) {
+            error.display(py);
+            panic!("Python code failed");
+        }
+    });
+}
+
+#[test]
+fn step_observation_and_reward() {
+    run_python(
+        r#"
+# Waits for a key, draws its font sprite and stores its BCD at 0x300.
+rom = bytes([
+    0xF0, 0x0A,  # LD V0, K
+    0xF0, 0x29,  # LD F, V0
+    0xD1, 0x15,  # DRW V1, V1, 5
+    0xA3, 0x00,  # LD I, 0x300
+    0xF0, 0x33,  # LD B, V0
+    0x12, 0x0A,  # JP 0x20A
+])
+env = chip8vm.Chip8Env(rom, frame_skip=1, seed=7)
+env.set_reward_fn(lambda memory: float(memory[0x302]))
+
+observation = env.reset()
+view = memoryview(observation)
+assert view.shape == (32, 64)
+assert view.format == "B"
+assert not any(observation.tobytes())
+
+observation, reward, terminated, truncated, info = env.step(None)
+assert reward == 0.0 and not terminated and not truncated
+
+observation, reward, terminated, truncated, info = env.step(0x7)
+view = memoryview(observation)
+assert view.tolist()[0][:5] == [1, 1, 1, 1, 0]
+assert reward == 7.0
+assert not terminated and not truncated
+assert info["frames"] == 2
+assert chip8vm.Chip8Env.ACTIONS == 16
+"#,
+    );
+}
+
+#[test]
+fn done_and_reset() {
+    run_python(
+        r#"
+env = chip8vm.Chip8Env(bytes([0x60, 0x05, 0x00, 0x00]))
+env.set_done_fn(lambda memory: False)
+
+observation, reward, terminated, truncated, info = env.step(None)
+assert terminated and not truncated
+
+env.reset(seed=3)
+observation, reward, terminated, truncated, info = env.step()
+assert terminated and not truncated
+
+try:
+    env.step(16)
+    assert False
+except ValueError:
+    pass
+
+try:
+    chip8vm.Chip8Env(bytes(4096))
+    assert False
+except ValueError:
+    pass
+"#,
+    );
+}
+
+#[test]
+fn termination_and_truncation() {
+    run_python(
+        r#"
+# Counts forever at 0x300.
+rom = bytes([
+    0xA3, 0x00,  # LD I, 0x300
+    0x60, 0x00,  # LD V0, 0
+    0x70, 0x01,  # ADD V0, 1
+    0xF0, 0x55,  # LD [I], V0
+    0x12, 0x04,  # JP 0x204
+])
+env = chip8vm.Chip8Env(rom, frame_skip=1, ticks_per_frame=4, max_frames=3)
+
+for frame in range(2):
+    observation, reward, terminated, truncated, info = env.step()
+    assert not terminated and not truncated
+observation, reward, terminated, truncated, info = env.step()
+assert not terminated and truncated
+assert info["frames"] == 3
+
+env.reset()
+env.set_done_fn(lambda memory: True)
+observation, reward, terminated, truncated, info = env.step()
+assert terminated and not truncated
+"#,
+    );
+}
+
+#[test]
+fn matches_rust_env() {
+    use chip8vm::env::{Env, EnvConfig, Never, NoReward, NO_ACTION};
+
+    let rom = include_bytes!("../../games/BRIX");
+    let actions = [Some(0x4), Some(0x6), None];
+    let mut env = Env::new(&rom[..], 5, EnvConfig::default(), NoReward, Never).unwrap();
+    let mut observations = Vec::new();
+    for step in 0..100 {
+        let action = actions[step % actions.len()].map_or(NO_ACTION, |key| key as usize);
+        let observation = env.step(action).observation;
+        let mut pixels = Vec::new();
+        for y in 0..chip8vm::SCREEN_HEIGHT {
+            for x in 0..chip8vm::SCREEN_WIDTH {
+                pixels.push(observation.is_on(x, y) as u8);
+            }
+        }
+        observations.push(pixels);
+    }
+
+    Python::attach(|py| {
+        let module = PyModule::new(py, "chip8vm").unwrap();
+        module.add_class::<Chip8Env>().unwrap();
+        module.add_class::<Frame>().unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("chip8vm", module).unwrap();
+        globals.set_item("rom", &rom[..]).unwrap();
+
+        let code = CString::new(
+            r#"
+env = chip8vm.Chip8Env(rom, seed=5)
+actions = [0x4, 0x6, None]
+observations = [env.step(actions[step % 3])[0].tobytes() for step in range(100)]
+"#,
+        )
+        .unwrap();
+        py.run(&code, Some(&globals), None).unwrap();
+
+        let python: Vec<Vec<u8>> = globals.get_item("observations").unwrap().unwrap().extract().unwrap();
+        assert_eq!(python, observations);
+    });
+}
@@ -0,0 +1,151 @@
+//! The chip8vm-wasm crate exposes the chip8vm::chip::Chip VM to JavaScript through
+//! wasm-bindgen, so programs can be run in a browser or in Node.
+//!
+//! The Emulator struct owns the VM together with a framebuffer, a keypad and a seeded random
+//! number generator. The host is expected to call Emulator::run_frame at 60Hz and to render
+//! the framebuffer after every frame.
+
+use wasm_bindgen::prelude::*;
+
+use chip8vm::chip::Chip;
+use chip8vm::framebuffer::{FrameBuffer, FRAMEBUFFER_SIZE};
+use chip8vm::palette::{Palette, RGBA_SIZE};
+use chip8vm::{KEYPAD_NUM_KEYS, PROGRAM_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Default number of instructions executed per frame.
+const TICKS_X_FRAME: u32 = 10;
+
+/// Xorshift random number generator, seeded by the host.
+struct Random {
+    state: u32,
+}
+
+impl chip8vm::Random for Random {
+    fn range(&mut self) -> u8 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state >> 24) as u8
+    }
+}
+
+/// Keypad with the key states set by the host.
+#[derive(Default)]
+struct Keypad {
+    keys: [bool; KEYPAD_NUM_KEYS],
+}
+
+impl chip8vm::Keypad for Keypad {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.keys[keycode as usize & 0xF]
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        self.keys.iter().position(|key| *key).map(|keycode| keycode as u8)
+    }
+}
+
+/// Chip8 VM emulator exported to JavaScript.
+#[wasm_bindgen]
+pub struct Emulator {
+    chip: Chip,
+    random: Random,
+    screen: FrameBuffer,
+    keypad: Keypad,
+    palette: Palette,
+    ticks: u32,
+    rgba: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Emulator {
+    /// Creates a new emulator. The seed initializes the random number generator, so runs with
+    /// the same seed and input are reproducible.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> Emulator {
+        Emulator {
+            chip: Chip::default(),
+            random: Random { state: seed.max(1) },
+            screen: FrameBuffer::default(),
+            keypad: Keypad::default(),
+            palette: Palette::default(),
+            ticks: TICKS_X_FRAME,
+            rgba: vec![0; RGBA_SIZE],
+        }
+    }
+
+    /// Loads a ROM, returning false if it does not fit in the program memory.
+    pub fn load_rom(&mut self, rom: &[u8]) -> bool {
+        if rom.len() > PROGRAM_SIZE {
+            return false;
+        }
+
+        self.chip = Chip::default();
+        self.chip.load_rom(rom);
+        self.screen = FrameBuffer::default();
+        true
+    }
+
+    /// Sets the number of instructions executed per frame.
+    pub fn set_ticks_per_frame(&mut self, ticks: u32) {
+        self.ticks = ticks;
+    }
+
+    /// Sets the background and foreground colors, as 0xRRGGBB values.
+    pub fn set_colors(&mut self, background: u32, foreground: u32) {
+        self.palette.colors[0] = rgb(background);
+        self.palette.colors[1] = rgb(foreground);
+    }
+
+    /// Runs one 60Hz frame, executing the configured number of instructions and decreasing the
+    /// timers. Returns false when the program stops.
+    pub fn run_frame(&mut self) -> bool {
+        for _i in 0..self.ticks {
+            if !self.chip.tick(&mut self.random, &mut self.screen, &self.keypad) {
+                return false;
+            }
+        }
+        self.chip.tick_timers();
+        true
+    }
+
+    /// Marks a key as pressed. From 0x0 to 0xF.
+    pub fn key_down(&mut self, keycode: u8) {
+        self.keypad.keys[keycode as usize & 0xF] = true;
+    }
+
+    /// Marks a key as released. From 0x0 to 0xF.
+    pub fn key_up(&mut self, keycode: u8) {
+        self.keypad.keys[keycode as usize & 0xF] = false;
+    }
+
+    /// Returns the screen width in pixels.
+    pub fn width(&self) -> usize {
+        SCREEN_WIDTH
+    }
+
+    /// Returns the screen height in pixels.
+    pub fn height(&self) -> usize {
+        SCREEN_HEIGHT
+    }
+
+    /// Returns a copy of the packed framebuffer, one bit per pixel.
+    pub fn framebuffer(&self) -> Vec<u8> {
+        self.screen.data().to_vec()
+    }
+
+    /// Returns the size of the packed framebuffer.
+    pub fn framebuffer_size(&self) -> usize {
+        FRAMEBUFFER_SIZE
+    }
+
+    /// Returns the screen as RGBA pixels, ready to be used in an ImageData.
+    pub fn rgba(&mut self) -> Vec<u8> {
+        self.palette.to_rgba(&self.screen, &mut self.rgba);
+        self.rgba.clone()
+    }
+}
+
+fn rgb(color: u32) -> [u8; 3] {
+    [(color >> 16) as u8, (color >> 8) as u8, color as u8]
+}
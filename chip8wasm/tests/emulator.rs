@@ -0,0 +1,67 @@
+//! Tests for the exported Emulator API. They run natively with `cargo test` and headlessly in
+//! Node with `wasm-pack test --node`.
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+use chip8vm_wasm::Emulator;
+
+// Draws the sprite of the key pressed at 0,0 and loops forever.
+const KEY_ROM: [u8; 10] = [
+    0xF0, 0x0A, // LD V0, K
+    0xF0, 0x29, // LD F, V0
+    0xD1, 0x15, // DRW V1, V1, 5
+    0x12, 0x06, // JP 0x206
+    0x00, 0x00,
+];
+
+#[test]
+fn load_rom_too_big() {
+    let mut emulator = Emulator::new(1);
+
+    assert!(!emulator.load_rom(&[0; 4096]));
+}
+
+#[test]
+fn run_frame_stops_on_empty_program() {
+    let mut emulator = Emulator::new(1);
+    emulator.load_rom(&[0x00, 0x00]);
+
+    assert!(!emulator.run_frame());
+}
+
+#[test]
+fn key_and_framebuffer() {
+    let mut emulator = Emulator::new(1);
+    emulator.load_rom(&KEY_ROM);
+
+    assert!(emulator.run_frame());
+    assert!(emulator.framebuffer().iter().all(|byte| *byte == 0));
+
+    emulator.key_down(0x1);
+    assert!(emulator.run_frame());
+    emulator.key_up(0x1);
+
+    let framebuffer = emulator.framebuffer();
+    assert_eq!(framebuffer.len(), emulator.framebuffer_size());
+    // Sprite of the character 1, drawn in the first 5 rows.
+    let row_bytes = emulator.width() / 8;
+    assert_eq!(framebuffer[0], 0b_00100000);
+    assert_eq!(framebuffer[row_bytes], 0b_01100000);
+    assert_eq!(framebuffer[4 * row_bytes], 0b_01110000);
+}
+
+#[test]
+fn rgba_uses_colors() {
+    let mut emulator = Emulator::new(1);
+    emulator.load_rom(&KEY_ROM);
+    emulator.set_colors(0x112233, 0xFFCC00);
+    emulator.key_down(0x8);
+    emulator.run_frame();
+
+    let rgba = emulator.rgba();
+
+    assert_eq!(rgba.len(), emulator.width() * emulator.height() * 4);
+    assert_eq!(rgba[0..4], [0xFF, 0xCC, 0x00, 0xFF]);
+    assert_eq!(rgba[4 * 4..4 * 4 + 4], [0x11, 0x22, 0x33, 0xFF]);
+}
@@ -0,0 +1,71 @@
+// Build the package with `wasm-pack build --target web --out-dir www/pkg` from the chip8wasm
+// folder and serve the www folder with any static file server.
+import init, { Emulator } from "./pkg/chip8vm_wasm.js";
+
+const KEYMAP = {
+  "1": 0x1, "2": 0x2, "3": 0x3, "4": 0xC,
+  "q": 0x4, "w": 0x5, "e": 0x6, "r": 0xD,
+  "a": 0x7, "s": 0x8, "d": 0x9, "f": 0xE,
+  "z": 0xA, "x": 0x0, "c": 0xB, "v": 0xF,
+};
+
+async function main() {
+  await init();
+
+  const emulator = new Emulator(Date.now() >>> 0);
+  const canvas = document.getElementById("screen");
+  const context = canvas.getContext("2d");
+  const image = context.createImageData(emulator.width(), emulator.height());
+  let running = false;
+
+  document.getElementById("rom").addEventListener("change", async (event) => {
+    const file = event.target.files[0];
+    const rom = new Uint8Array(await file.arrayBuffer());
+    running = emulator.load_rom(rom);
+    if (!running) {
+      alert("The ROM does not fit in the program memory.");
+    }
+  });
+
+  document.addEventListener("keydown", (event) => {
+    const key = KEYMAP[event.key.toLowerCase()];
+    if (key !== undefined) {
+      emulator.key_down(key);
+    }
+  });
+
+  document.addEventListener("keyup", (event) => {
+    const key = KEYMAP[event.key.toLowerCase()];
+    if (key !== undefined) {
+      emulator.key_up(key);
+    }
+  });
+
+  // Emulation runs at 60 frames per second whatever the display refresh rate, catching up
+  // at most MAX_FRAMES frames per callback, so background tabs do not run a burst of frames
+  // when they are shown again.
+  const FRAME_TIME = 1000 / 60;
+  const MAX_FRAMES = 4;
+  let elapsed = 0;
+  let last = null;
+
+  function frame(now) {
+    elapsed += last === null ? 0 : now - last;
+    last = now;
+
+    const frames = Math.floor(elapsed / FRAME_TIME);
+    elapsed -= frames * FRAME_TIME;
+    if (running && frames > 0) {
+      for (let i = 0; i < Math.min(frames, MAX_FRAMES) && running; i++) {
+        running = emulator.run_frame();
+      }
+      image.data.set(emulator.rgba());
+      context.putImageData(image, 0, 0);
+    }
+    requestAnimationFrame(frame);
+  }
+
+  requestAnimationFrame(frame);
+}
+
+main();
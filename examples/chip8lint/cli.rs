@@ -0,0 +1,21 @@
+use argh::FromArgs;
+
+#[derive(FromArgs)]
+/// chip8lint reports problems of chip8 programs before running them.
+pub struct Cli {
+    /// paths to the program files
+    #[argh(positional)]
+    pub program_paths: Vec<String>,
+
+    /// do not report instructions that depend on the interpreter quirks
+    #[argh(switch)]
+    pub no_quirks: bool,
+
+    /// do not report unreachable bytes
+    #[argh(switch)]
+    pub no_unreachable: bool,
+}
+
+pub fn get_options() -> Cli {
+    argh::from_env()
+}
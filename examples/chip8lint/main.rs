@@ -0,0 +1,49 @@
+use std::fs;
+use std::process::exit;
+
+mod cli;
+
+use chip8vm::lint::{lint, Issue};
+
+/// Returns true if the issue stops or corrupts the execution in any interpreter.
+fn is_error(issue: &Issue) -> bool {
+    !matches!(issue, Issue::Unreachable { .. } | Issue::QuirkSpecific { .. } | Issue::SelfModifying { .. })
+}
+
+fn main() {
+    let options = cli::get_options();
+    let mut errors = 0;
+
+    for path in options.program_paths.iter() {
+        let program = match fs::read(path) {
+            Ok(program) => program,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                errors += 1;
+                continue;
+            }
+        };
+
+        let mut report = |issue: Issue| {
+            let hidden = match issue {
+                Issue::QuirkSpecific { .. } => options.no_quirks,
+                Issue::Unreachable { .. } => options.no_unreachable,
+                _ => false,
+            };
+            if is_error(&issue) {
+                errors += 1;
+                println!("{}: error: {}", path, issue);
+            } else if !hidden {
+                println!("{}: warning: {}", path, issue);
+            }
+        };
+        if !lint(&program, &mut report) {
+            println!("{}: error: the program does not fit in memory", path);
+            errors += 1;
+        }
+    }
+
+    if errors > 0 {
+        exit(1);
+    }
+}
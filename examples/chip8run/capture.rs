@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chip8vm::capture::{write_pbm, write_png, GifRecorder};
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::palette::Palette;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ImageFormat {
+    Png,
+    Pbm,
+}
+
+struct Recording {
+    recorder: GifRecorder,
+    data: Vec<u8>,
+    path: PathBuf,
+}
+
+pub struct Capture {
+    name: String,
+    format: ImageFormat,
+    palette: Palette,
+    scale: usize,
+    screenshots: u32,
+    recordings: u32,
+    recording: Option<Recording>,
+}
+
+impl Capture {
+    pub fn new(name: &str, format: ImageFormat, palette: Palette, scale: u16) -> Capture {
+        Capture {
+            name: name.to_string(),
+            format,
+            palette,
+            scale: scale as usize,
+            screenshots: 0,
+            recordings: 0,
+            recording: None,
+        }
+    }
+
+    pub fn screenshot(&mut self, framebuffer: &FrameBuffer) {
+        self.screenshots += 1;
+        let mut data = Vec::new();
+        let mut output = |bytes: &[u8]| data.extend_from_slice(bytes);
+        let extension = match self.format {
+            ImageFormat::Png => {
+                if !write_png(framebuffer, &self.palette, self.scale, &mut output) {
+                    eprintln!("Unable to save screenshot: invalid scale {}", self.scale);
+                    return;
+                }
+                "png"
+            }
+            ImageFormat::Pbm => {
+                write_pbm(framebuffer, &mut output);
+                "pbm"
+            }
+        };
+
+        let path = format!("{}-{}.{}", self.name, self.screenshots, extension);
+        match fs::write(&path, data) {
+            Ok(()) => println!("Screenshot saved to {}", path),
+            Err(error) => eprintln!("Unable to save screenshot {}: {}", path, error),
+        }
+    }
+
+    pub fn toggle_recording(&mut self) {
+        if self.recording.is_some() {
+            self.stop_recording();
+        } else {
+            self.recordings += 1;
+            let path = PathBuf::from(format!("{}-{}.gif", self.name, self.recordings));
+            self.start_recording(path);
+        }
+    }
+
+    pub fn start_recording(&mut self, path: PathBuf) {
+        let mut data = Vec::new();
+        let recorder = match GifRecorder::start(self.palette, self.scale, &mut |bytes: &[u8]| {
+            data.extend_from_slice(bytes)
+        }) {
+            Some(recorder) => recorder,
+            None => {
+                eprintln!("Unable to record {}: invalid scale {}", path.display(), self.scale);
+                return;
+            }
+        };
+        println!("Recording to {}", path.display());
+        self.recording = Some(Recording { recorder, data, path });
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some(mut recording) = self.recording.take() {
+            let data = &mut recording.data;
+            recording.recorder.finish(&mut |bytes: &[u8]| data.extend_from_slice(bytes));
+            match fs::write(&recording.path, &recording.data) {
+                Ok(()) => println!("Recording saved to {}", recording.path.display()),
+                Err(error) => eprintln!("Unable to save recording {}: {}", recording.path.display(), error),
+            }
+        }
+    }
+
+    pub fn frame(&mut self, framebuffer: &FrameBuffer) {
+        if let Some(recording) = &mut self.recording {
+            let data = &mut recording.data;
+            recording.recorder.frame(framebuffer, &mut |bytes: &[u8]| data.extend_from_slice(bytes));
+        }
+    }
+}
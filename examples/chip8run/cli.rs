@@ -1,5 +1,11 @@
 use argh::FromArgs;
 
+use chip8vm::filter::Persistence;
+use chip8vm::palette::{Palette, Rgb};
+
+use crate::capture::ImageFormat;
+use crate::screen::Effect;
+
 #[derive(FromArgs)]
 /// chip8run is a chip8 emulator.
 pub struct Cli {
@@ -10,6 +16,103 @@ pub struct Cli {
     /// screen resolution scale
     #[argh(option, default = "10")]
     pub scale: u16,
+
+    /// phosphor persistence filter: none, decay or accumulate (toggle with P)
+    #[argh(option, default = "Persistence::None", from_str_fn(parse_persistence))]
+    pub persistence: Persistence,
+
+    /// colour palette: monochrome, green, amber, lcd or octo. Defaults to the colours of the
+    /// program in the database, or monochrome
+    #[argh(option, from_str_fn(parse_palette))]
+    pub palette: Option<Palette>,
+
+    /// foreground colour as RRGGBB hex, overriding the palette
+    #[argh(option, from_str_fn(parse_color))]
+    pub foreground: Option<Rgb>,
+
+    /// background colour as RRGGBB hex, overriding the palette
+    #[argh(option, from_str_fn(parse_color))]
+    pub background: Option<Rgb>,
+
+    /// display effect: none, grid or scanlines
+    #[argh(option, default = "Effect::None", from_str_fn(parse_effect))]
+    pub effect: Effect,
+
+    /// screenshot format taken with F12: png or pbm
+    #[argh(option, default = "ImageFormat::Png", from_str_fn(parse_image_format))]
+    pub screenshot_format: ImageFormat,
+
+    /// record the session as an animated GIF in the provided path (toggle with F9)
+    #[argh(option)]
+    pub record: Option<String>,
+
+    /// write the program to the provided path as a raw (.ch8), Intel HEX (.hex), hex dump (.txt)
+    /// or base64 (.b64) image, by extension, and exit
+    #[argh(option)]
+    pub export: Option<String>,
+
+    /// do not apply the quirks, speed, keys and colours of the program in the ROM database
+    #[argh(switch)]
+    pub no_database: bool,
+}
+
+fn parse_persistence(value: &str) -> Result<Persistence, String> {
+    match value {
+        "none" => Ok(Persistence::None),
+        "decay" => Ok(Persistence::Decay { factor: 192 }),
+        "accumulate" => Ok(Persistence::Accumulate { frames: 3 }),
+        _ => Err(format!("unknown persistence filter: {}", value)),
+    }
+}
+
+fn parse_palette(value: &str) -> Result<Palette, String> {
+    match value {
+        "monochrome" => Ok(Palette::MONOCHROME),
+        "green" => Ok(Palette::CLASSIC_GREEN),
+        "amber" => Ok(Palette::AMBER),
+        "lcd" => Ok(Palette::LCD),
+        "octo" => Ok(Palette::OCTO),
+        _ => Err(format!("unknown palette: {}", value)),
+    }
+}
+
+fn parse_color(value: &str) -> Result<Rgb, String> {
+    let value = value.trim_start_matches('#');
+    let color = u32::from_str_radix(value, 16).map_err(|_| format!("invalid colour: {}", value))?;
+    if value.len() != 6 {
+        return Err(format!("invalid colour: {}", value));
+    }
+    Ok([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+}
+
+fn parse_effect(value: &str) -> Result<Effect, String> {
+    match value {
+        "none" => Ok(Effect::None),
+        "grid" => Ok(Effect::Grid),
+        "scanlines" => Ok(Effect::Scanlines),
+        _ => Err(format!("unknown effect: {}", value)),
+    }
+}
+
+fn parse_image_format(value: &str) -> Result<ImageFormat, String> {
+    match value {
+        "png" => Ok(ImageFormat::Png),
+        "pbm" => Ok(ImageFormat::Pbm),
+        _ => Err(format!("unknown screenshot format: {}", value)),
+    }
+}
+
+impl Cli {
+    pub fn palette(&self, program_palette: Option<Palette>) -> Palette {
+        let mut palette = self.palette.or(program_palette).unwrap_or(Palette::MONOCHROME);
+        if let Some(background) = self.background {
+            palette.colors[0] = background;
+        }
+        if let Some(foreground) = self.foreground {
+            palette.colors[1] = foreground;
+        }
+        palette
+    }
 }
 
 pub fn get_options() -> Cli {
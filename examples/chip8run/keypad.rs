@@ -1,9 +1,7 @@
-use chip8vm;
-
 #[derive(Copy, Clone, PartialEq)]
 pub enum KeyState {
-    PRESSED,
-    RELEASED,
+    Pressed,
+    Released,
 }
 
 pub struct Keypad {
@@ -13,34 +11,34 @@ pub struct Keypad {
 impl Keypad {
     pub fn new() -> Keypad {
         Keypad {
-            keys: [KeyState::RELEASED; chip8vm::KEYPAD_NUM_KEYS],
+            keys: [KeyState::Released; chip8vm::KEYPAD_NUM_KEYS],
         }
     }
 
     pub fn key_pressed(&mut self, keycode: u8) {
-        self.keys[keycode as usize] = KeyState::PRESSED;
+        self.keys[keycode as usize] = KeyState::Pressed;
     }
 
     pub fn key_released(&mut self, keycode: u8) {
-        self.keys[keycode as usize] = KeyState::RELEASED;
+        self.keys[keycode as usize] = KeyState::Released;
     }
 }
 
 impl chip8vm::Keypad for Keypad {
     fn is_pressed(&self, keycode: u8) -> bool {
         match self.keys[keycode as usize] {
-            KeyState::PRESSED => true,
-            KeyState::RELEASED => false,
+            KeyState::Pressed => true,
+            KeyState::Released => false,
         }
     }
 
     fn pressed_key(&self) -> Option<u8> {
         for (keycode, state) in self.keys.iter().enumerate() {
             match state {
-                KeyState::PRESSED => {
+                KeyState::Pressed => {
                     return Some(keycode as u8);
                 }
-                KeyState::RELEASED => continue,
+                KeyState::Released => continue,
             }
         }
 
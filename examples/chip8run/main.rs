@@ -1,52 +1,110 @@
-use ggez;
 use ggez::conf::WindowMode;
-use ggez::event::{quit, KeyCode};
+use ggez::event::{quit, KeyCode, KeyMods};
 use ggez::graphics::set_window_title;
-use ggez::{event, graphics, input, Context, ContextBuilder, GameResult};
+use ggez::{event, graphics, input, timer, Context, ContextBuilder, GameResult};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+mod capture;
 mod cli;
 mod keypad;
 mod random;
 mod screen;
 
-use chip8vm::{chip::Chip, PROGRAM_SIZE};
+use capture::*;
+use chip8vm::capture::FRAME_RATE;
+use chip8vm::chip::{Chip, Scrub};
+use chip8vm::container::{self, Cartridge, Config, LzwTables};
+use chip8vm::database::{self, KeyMap};
+use chip8vm::formats::Format;
 use keypad::*;
 use random::*;
 use screen::*;
 
-const TICKS_X_FRAME: u32 = 10;
+/// Size of the buffer receiving the data of Octo cartridges.
+const OCTO_CART_BUFFER_SIZE: usize = 1 << 20;
+
+/// Keyboard keys simulating the CHIP-8 keypad layout.
+const KEYS: [(KeyCode, u8); chip8vm::KEYPAD_NUM_KEYS] = [
+    (KeyCode::Key1, 0x1),
+    (KeyCode::Key2, 0x2),
+    (KeyCode::Key3, 0x3),
+    (KeyCode::Key4, 0xC),
+    (KeyCode::Q, 0x4),
+    (KeyCode::W, 0x5),
+    (KeyCode::E, 0x6),
+    (KeyCode::R, 0xD),
+    (KeyCode::A, 0x7),
+    (KeyCode::S, 0x8),
+    (KeyCode::D, 0x9),
+    (KeyCode::F, 0xE),
+    (KeyCode::Z, 0xA),
+    (KeyCode::X, 0x0),
+    (KeyCode::C, 0xB),
+    (KeyCode::V, 0xF),
+];
 
 struct Chip8Run {
     chip: Chip,
     random: Random,
     screen: Screen,
     keypad: Keypad,
+    capture: Capture,
+    program: Vec<u8>,
+    ticks_x_frame: u32,
+    keys: KeyMap,
 }
 
 impl Chip8Run {
-    fn new(context: &mut Context, scale: u16) -> GameResult<Chip8Run> {
+    fn new(context: &mut Context, name: &str, options: &cli::Cli, config: &Config) -> GameResult<Chip8Run> {
         let chip = Chip::default();
+        let palette = options.palette(config.palette);
         let random = Random {};
-        let screen = Screen::new(context, scale)?;
+        let screen = Screen::new(
+            context,
+            options.scale,
+            palette,
+            options.effect,
+            options.persistence,
+        )?;
         let keypad = Keypad::new();
+        let mut capture = Capture::new(name, options.screenshot_format, palette, options.scale);
+        if let Some(path) = &options.record {
+            capture.start_recording(PathBuf::from(path));
+        }
         let chip8 = Chip8Run {
             chip,
             random,
             screen,
             keypad,
+            capture,
+            program: Vec::new(),
+            ticks_x_frame: config.ticks_per_frame(),
+            keys: config.keys,
         };
         Ok(chip8)
     }
 
-    fn load_program(&mut self, program_path: String) {
-        let program = fs::read(program_path).expect("Unable to load program.");
-        let mut program_code: [u8; PROGRAM_SIZE] = [0; PROGRAM_SIZE];
-        for (i, data) in program.iter().enumerate() {
-            program_code[i] = *data;
-        }
-        self.chip.load_program(program_code);
+    fn load_program(&mut self, cartridge: &Cartridge) {
+        cartridge.load(&mut self.chip);
+        self.program = cartridge.rom().to_vec();
+    }
+
+    /// Returns the keyboard keys of the program actions, with the CHIP-8 keys they press.
+    fn action_keys(&self) -> [(KeyCode, Option<u8>); 6] {
+        [
+            (KeyCode::Up, self.keys.up),
+            (KeyCode::Down, self.keys.down),
+            (KeyCode::Left, self.keys.left),
+            (KeyCode::Right, self.keys.right),
+            (KeyCode::Space, self.keys.a),
+            (KeyCode::Return, self.keys.b),
+        ]
+    }
+
+    fn reload_program(&mut self) {
+        chip8vm::Screen::clear(&mut self.screen);
+        self.chip.reload(&self.program, Scrub::Zeros, &mut self.random);
     }
 }
 
@@ -57,110 +115,35 @@ impl event::EventHandler for Chip8Run {
             quit(context);
         }
 
-        if input::keyboard::is_key_pressed(context, KeyCode::Key1) {
-            self.keypad.key_pressed(0x1);
-        } else {
-            self.keypad.key_released(0x1);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::Key2) {
-            self.keypad.key_pressed(0x2);
-        } else {
-            self.keypad.key_released(0x2);
+        let mut pressed = [false; chip8vm::KEYPAD_NUM_KEYS];
+        for (keycode, key) in KEYS.iter() {
+            pressed[*key as usize] |= input::keyboard::is_key_pressed(context, *keycode);
         }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::Key3) {
-            self.keypad.key_pressed(0x3);
-        } else {
-            self.keypad.key_released(0x3);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::Key4) {
-            self.keypad.key_pressed(0xC);
-        } else {
-            self.keypad.key_released(0xC);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::Q) {
-            self.keypad.key_pressed(0x4);
-        } else {
-            self.keypad.key_released(0x4);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::W) {
-            self.keypad.key_pressed(0x5);
-        } else {
-            self.keypad.key_released(0x5);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::E) {
-            self.keypad.key_pressed(0x6);
-        } else {
-            self.keypad.key_released(0x6);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::R) {
-            self.keypad.key_pressed(0xD);
-        } else {
-            self.keypad.key_released(0xD);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::A) {
-            self.keypad.key_pressed(0x7);
-        } else {
-            self.keypad.key_released(0x7);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::S) {
-            self.keypad.key_pressed(0x8);
-        } else {
-            self.keypad.key_released(0x8);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::D) {
-            self.keypad.key_pressed(0x9);
-        } else {
-            self.keypad.key_released(0x9);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::F) {
-            self.keypad.key_pressed(0xE);
-        } else {
-            self.keypad.key_released(0xE);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::Z) {
-            self.keypad.key_pressed(0xA);
-        } else {
-            self.keypad.key_released(0xA);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::X) {
-            self.keypad.key_pressed(0x0);
-        } else {
-            self.keypad.key_released(0x0);
-        }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::C) {
-            self.keypad.key_pressed(0xB);
-        } else {
-            self.keypad.key_released(0xB);
+        for (keycode, key) in self.action_keys() {
+            if let Some(key) = key {
+                pressed[key as usize] |= input::keyboard::is_key_pressed(context, keycode);
+            }
         }
-
-        if input::keyboard::is_key_pressed(context, KeyCode::V) {
-            self.keypad.key_pressed(0xF);
-        } else {
-            self.keypad.key_released(0xF);
+        for (key, pressed) in pressed.iter().enumerate() {
+            if *pressed {
+                self.keypad.key_pressed(key as u8);
+            } else {
+                self.keypad.key_released(key as u8);
+            }
         }
 
-        // emulate cpu
-        for _i in 0..TICKS_X_FRAME {
-            if !self
-                .chip
-                .tick(&mut self.random, &mut self.screen, &self.keypad)
-            {
-                quit(context);
+        // emulate cpu and timers, recording a frame every 60Hz timer tick
+        while timer::check_update_time(context, FRAME_RATE) {
+            for _i in 0..self.ticks_x_frame {
+                if !self
+                    .chip
+                    .tick(&mut self.random, &mut self.screen, &self.keypad)
+                {
+                    quit(context);
+                }
             }
+            self.chip.tick_timers();
+            self.capture.frame(self.screen.framebuffer());
         }
 
         Ok(())
@@ -169,37 +152,102 @@ impl event::EventHandler for Chip8Run {
     fn draw(&mut self, context: &mut Context) -> GameResult {
         //println!("delta = {}ns", timer::delta(ctx).subsec_nanos());
 
-        // emulate timers
-        self.chip.tick_timers();
-
         // draw screen
-        graphics::clear(context, graphics::BLACK);
         self.screen.draw(context)?;
         graphics::present(context)
     }
+
+    fn key_down_event(&mut self, _context: &mut Context, keycode: KeyCode, _keymods: KeyMods, repeat: bool) {
+        if repeat {
+            return;
+        }
+
+        match keycode {
+            KeyCode::P => self.screen.toggle_persistence(),
+            KeyCode::F5 => self.reload_program(),
+            KeyCode::F9 => self.capture.toggle_recording(),
+            KeyCode::F12 => self.capture.screenshot(self.screen.framebuffer()),
+            _ => (),
+        }
+    }
+}
+
+/// Loads the program with its configuration: Octo cartridges by their .gif extension and
+/// binaries in the image format of their extension otherwise, followed by the options of a
+/// .json or .toml file next to the program.
+fn load_cartridge(path: &Path, no_database: bool) -> Result<Cartridge, String> {
+    let data = fs::read(path).map_err(|error| error.to_string())?;
+    let mut cartridge = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("gif") => {
+            let mut tables = Box::new(LzwTables::new());
+            container::load_octo_cart(&data, &mut vec![0; OCTO_CART_BUFFER_SIZE], &mut tables)
+        },
+        extension => {
+            let format = extension.and_then(Format::from_extension).unwrap_or(Format::Raw);
+            let mut buffer = [0; chip8vm::PROGRAM_SIZE];
+            let size = format.import(&data, &mut buffer).map_err(|error| error.to_string())?;
+            container::load_binary(&buffer[..size]).map(|mut cartridge| {
+                if no_database {
+                    cartridge.config = Config::default();
+                }
+                cartridge
+            })
+        }
+    }
+    .map_err(|error| error.to_string())?;
+
+    if let Ok(text) = fs::read_to_string(path.with_extension("json")) {
+        cartridge.config.apply_json(&text).map_err(|error| error.to_string())?;
+    }
+    if let Ok(text) = fs::read_to_string(path.with_extension("toml")) {
+        cartridge.config.apply_toml(&text).map_err(|error| error.to_string())?;
+    }
+    Ok(cartridge)
+}
+
+/// Writes the program in the image format of the extension of the path.
+fn export_program(path: &Path, rom: &[u8]) -> Result<(), String> {
+    let format = path.extension().and_then(|extension| extension.to_str()).and_then(Format::from_extension);
+    let format = format.ok_or_else(|| format!("unknown image format: {}", path.display()))?;
+    let mut image = Vec::new();
+    format.export(rom, &mut |bytes| image.extend_from_slice(bytes));
+    fs::write(path, image).map_err(|error| error.to_string())
 }
 
 fn main() -> GameResult {
     let options = cli::get_options();
 
-    if options.scale > 30 {
-        panic!("scale parameter can not be greater than 30.");
+    let path = Path::new(options.program_path.as_str());
+    let name = path.file_stem().unwrap().to_str().unwrap();
+    let cartridge = load_cartridge(path, options.no_database).expect("Unable to load program.");
+
+    if let Some(export_path) = &options.export {
+        export_program(Path::new(export_path), cartridge.rom()).expect("Unable to export program.");
+        return Ok(());
+    }
+
+    if options.scale == 0 || options.scale > 30 {
+        panic!("scale parameter must be between 1 and 30.");
     }
 
     let window = WindowMode {
-        width: (64 * options.scale) as f32,
-        height: (32 * options.scale) as f32,
+        width: (chip8vm::SCREEN_WIDTH as u16 * options.scale) as f32,
+        height: (chip8vm::SCREEN_HEIGHT as u16 * options.scale) as f32,
         ..WindowMode::default()
     };
 
     let builder = ContextBuilder::new("chip8run", "chip8vm");
     let (context, event_loop) = &mut builder.window_mode(window).build()?;
 
-    let path = Path::new(options.program_path.as_str());
-    set_window_title(context, path.file_stem().unwrap().to_str().unwrap());
+    match database::lookup(cartridge.rom()) {
+        Some(info) if !options.no_database => set_window_title(context, &format!("{} - {}", info.title, info.author)),
+        _ => set_window_title(context, name),
+    }
 
-    let chip8 = &mut Chip8Run::new(context, options.scale)?;
-    chip8.load_program(options.program_path);
+    let chip8 = &mut Chip8Run::new(context, name, &options, &cartridge.config)?;
+    chip8.load_program(&cartridge);
 
-    event::run(context, event_loop, chip8)
+    let result = event::run(context, event_loop, chip8);
+    chip8.capture.stop_recording();
+    result
 }
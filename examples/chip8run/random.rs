@@ -1,5 +1,3 @@
-use chip8vm;
-
 use rand::prelude::*;
 
 pub struct Random {}
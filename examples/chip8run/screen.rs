@@ -1,80 +1,116 @@
+use ggez::graphics::{DrawMode, DrawParam, FilterMode, Image, Mesh, MeshBuilder, Rect};
 use ggez::{graphics, Context, GameResult};
 
-use chip8vm;
+use chip8vm::filter::{Persistence, PersistenceFilter};
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::palette::{Palette, RGBA_SIZE};
+use chip8vm::{SCREEN_HEIGHT, SCREEN_WIDTH};
 
-#[derive(PartialEq, Copy, Clone)]
-pub enum PixelState {
-    ON,
-    OFF,
+#[derive(Copy, Clone, PartialEq)]
+pub enum Effect {
+    None,
+    Grid,
+    Scanlines,
 }
 
 pub struct Screen {
-    display: Vec<graphics::Mesh>,
-    pixels: [PixelState; chip8vm::SCREEN_WIDTH * chip8vm::SCREEN_HEIGHT],
+    scale: u16,
+    palette: Palette,
+    overlay: Option<Mesh>,
+    rgba: Vec<u8>,
+    framebuffer: FrameBuffer,
+    filter: PersistenceFilter,
+    persistence: Persistence,
 }
 
 impl Screen {
-    pub fn new(context: &mut Context, scale: u16) -> GameResult<Screen> {
-        let mut display = Vec::new();
-
-        for y in 0..32 {
-            for x in 0..64 {
-                let rect = ggez::graphics::Rect::new(
-                    (x * scale) as f32,
-                    (y * scale) as f32,
-                    scale as f32,
-                    scale as f32,
-                );
-                let pixel = graphics::Mesh::new_rectangle(
-                    context,
-                    graphics::DrawMode::fill(),
-                    rect,
-                    graphics::WHITE,
-                )?;
-                display.push(pixel)
-            }
-        }
-
+    pub fn new(
+        context: &mut Context,
+        scale: u16,
+        palette: Palette,
+        effect: Effect,
+        persistence: Persistence,
+    ) -> GameResult<Screen> {
         Ok(Screen {
-            display,
-            pixels: [PixelState::OFF; chip8vm::SCREEN_WIDTH * chip8vm::SCREEN_HEIGHT],
+            scale,
+            palette,
+            overlay: Screen::build_overlay(context, scale as f32, effect)?,
+            rgba: vec![0; RGBA_SIZE],
+            framebuffer: FrameBuffer::default(),
+            filter: PersistenceFilter::new(persistence),
+            persistence,
         })
     }
 
-    pub fn draw(&self, context: &mut Context) -> GameResult {
-        for (i, pixel) in self.display.iter().enumerate() {
-            if self.is_on(i) {
-                graphics::draw(context, pixel, graphics::DrawParam::default())?;
-            }
+    pub fn toggle_persistence(&mut self) {
+        let mode = match self.filter.mode() {
+            Persistence::None => match self.persistence {
+                Persistence::None => Persistence::Decay { factor: 192 },
+                persistence => persistence,
+            },
+            _ => Persistence::None,
+        };
+        self.filter.set_mode(mode);
+    }
+
+    pub fn framebuffer(&self) -> &FrameBuffer {
+        &self.framebuffer
+    }
+
+    pub fn draw(&mut self, context: &mut Context) -> GameResult {
+        self.filter.apply(&self.framebuffer);
+
+        self.palette.intensities_to_rgba(self.filter.intensities(), &mut self.rgba);
+
+        let mut image = Image::from_rgba8(context, SCREEN_WIDTH as u16, SCREEN_HEIGHT as u16, &self.rgba)?;
+        image.set_filter(FilterMode::Nearest);
+        let scale = self.scale as f32;
+        graphics::draw(context, &image, DrawParam::default().scale([scale, scale]))?;
+
+        if let Some(overlay) = &self.overlay {
+            graphics::draw(context, overlay, DrawParam::default())?;
         }
 
         Ok(())
     }
 
-    fn is_on(&self, i: usize) -> bool {
-        if self.pixels[i] == PixelState::ON {
-            true
-        } else {
-            false
+    fn build_overlay(context: &mut Context, scale: f32, effect: Effect) -> GameResult<Option<Mesh>> {
+        let width = SCREEN_WIDTH as f32 * scale;
+        let height = SCREEN_HEIGHT as f32 * scale;
+        let color = graphics::Color::new(0.0, 0.0, 0.0, 0.35);
+        let thickness = (scale / 4.0).max(1.0);
+
+        let mut builder = MeshBuilder::new();
+        match effect {
+            Effect::None => return Ok(None),
+            Effect::Grid => {
+                for x in 0..SCREEN_WIDTH {
+                    let bounds = Rect::new(x as f32 * scale, 0.0, thickness, height);
+                    builder.rectangle(DrawMode::fill(), bounds, color);
+                }
+                for y in 0..SCREEN_HEIGHT {
+                    let bounds = Rect::new(0.0, y as f32 * scale, width, thickness);
+                    builder.rectangle(DrawMode::fill(), bounds, color);
+                }
+            }
+            Effect::Scanlines => {
+                for y in 0..SCREEN_HEIGHT {
+                    let bounds = Rect::new(0.0, (y as f32 + 1.0) * scale - thickness, width, thickness);
+                    builder.rectangle(DrawMode::fill(), bounds, color);
+                }
+            }
         }
+
+        Ok(Some(builder.build(context)?))
     }
 }
 
 impl chip8vm::Screen for Screen {
     fn clear(&mut self) {
-        for pixel in self.pixels.iter_mut() {
-            *pixel = PixelState::OFF;
-        }
+        self.framebuffer.clear();
     }
 
     fn draw(&mut self, x: u8, y: u8) -> bool {
-        let position = (y as usize * chip8vm::SCREEN_WIDTH) + x as usize;
-        if self.pixels[position] == PixelState::ON {
-            self.pixels[position] = PixelState::OFF;
-            true
-        } else {
-            self.pixels[position] = PixelState::ON;
-            false
-        }
+        self.framebuffer.draw(x, y)
     }
 }
@@ -0,0 +1,35 @@
+use argh::FromArgs;
+
+use crate::display::Mode;
+
+#[derive(FromArgs)]
+/// chip8tui is a chip8 emulator running in the terminal.
+pub struct Cli {
+    /// path to the program file
+    #[argh(positional)]
+    pub program_path: String,
+
+    /// character set used to draw the screen: half or braille
+    #[argh(option, default = "Mode::HalfBlock", from_str_fn(parse_mode))]
+    pub mode: Mode,
+
+    /// number of instructions executed per frame
+    #[argh(option, default = "10")]
+    pub ticks: u32,
+
+    /// run without input for the provided number of frames, then print the screen and exit
+    #[argh(option)]
+    pub frames: Option<u32>,
+}
+
+fn parse_mode(value: &str) -> Result<Mode, String> {
+    match value {
+        "half" => Ok(Mode::HalfBlock),
+        "braille" => Ok(Mode::Braille),
+        _ => Err(format!("unknown mode: {}", value)),
+    }
+}
+
+pub fn get_options() -> Cli {
+    argh::from_env()
+}
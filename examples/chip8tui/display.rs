@@ -0,0 +1,57 @@
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Mode {
+    HalfBlock,
+    Braille,
+}
+
+// Braille dot bits, indexed by row and column inside the 2x4 character cell.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+pub fn render(framebuffer: &FrameBuffer, mode: Mode) -> Vec<String> {
+    match mode {
+        Mode::HalfBlock => half_block(framebuffer),
+        Mode::Braille => braille(framebuffer),
+    }
+}
+
+fn half_block(framebuffer: &FrameBuffer) -> Vec<String> {
+    (0..SCREEN_HEIGHT / 2)
+        .map(|row| {
+            (0..SCREEN_WIDTH)
+                .map(|x| {
+                    let top = framebuffer.is_on(x, row * 2);
+                    let bottom = framebuffer.is_on(x, row * 2 + 1);
+                    match (top, bottom) {
+                        (false, false) => ' ',
+                        (true, false) => '\u{2580}',
+                        (false, true) => '\u{2584}',
+                        (true, true) => '\u{2588}',
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn braille(framebuffer: &FrameBuffer) -> Vec<String> {
+    (0..SCREEN_HEIGHT / 4)
+        .map(|row| {
+            (0..SCREEN_WIDTH / 2)
+                .map(|column| {
+                    let mut dots = 0;
+                    for (dy, bits) in BRAILLE_DOTS.iter().enumerate() {
+                        for (dx, bit) in bits.iter().enumerate() {
+                            if framebuffer.is_on(column * 2 + dx, row * 4 + dy) {
+                                dots |= bit;
+                            }
+                        }
+                    }
+                    std::char::from_u32(0x2800 + dots).unwrap()
+                })
+                .collect()
+        })
+        .collect()
+}
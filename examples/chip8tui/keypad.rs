@@ -0,0 +1,68 @@
+use crossterm::event::KeyCode;
+
+// Number of frames a key stays pressed after a key press event, for terminals that do not
+// report key releases.
+const HOLD_FRAMES: u8 = 15;
+
+pub struct Keypad {
+    keys: [u8; chip8vm::KEYPAD_NUM_KEYS],
+}
+
+impl Keypad {
+    pub fn new() -> Keypad {
+        Keypad {
+            keys: [0; chip8vm::KEYPAD_NUM_KEYS],
+        }
+    }
+
+    pub fn key_pressed(&mut self, keycode: u8) {
+        self.keys[keycode as usize] = HOLD_FRAMES;
+    }
+
+    pub fn key_released(&mut self, keycode: u8) {
+        self.keys[keycode as usize] = 0;
+    }
+
+    pub fn tick(&mut self) {
+        for key in self.keys.iter_mut() {
+            *key = key.saturating_sub(1);
+        }
+    }
+}
+
+impl chip8vm::Keypad for Keypad {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.keys[keycode as usize] > 0
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        self.keys.iter().position(|key| *key > 0).map(|keycode| keycode as u8)
+    }
+}
+
+pub fn map_key(code: KeyCode) -> Option<u8> {
+    let key = match code {
+        KeyCode::Char(key) => key.to_ascii_lowercase(),
+        _ => return None,
+    };
+
+    match key {
+        '1' => Some(0x1),
+        '2' => Some(0x2),
+        '3' => Some(0x3),
+        '4' => Some(0xC),
+        'q' => Some(0x4),
+        'w' => Some(0x5),
+        'e' => Some(0x6),
+        'r' => Some(0xD),
+        'a' => Some(0x7),
+        's' => Some(0x8),
+        'd' => Some(0x9),
+        'f' => Some(0xE),
+        'z' => Some(0xA),
+        'x' => Some(0x0),
+        'c' => Some(0xB),
+        'v' => Some(0xF),
+        _ => None,
+    }
+}
@@ -0,0 +1,152 @@
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    poll, read, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::style::Print;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, Clear, ClearType,
+    EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+use std::fs;
+use std::io::{self, Write};
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+mod cli;
+mod display;
+mod keypad;
+mod random;
+
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::{chip::Chip, PROGRAM_SIZE};
+use display::*;
+use keypad::*;
+use random::*;
+
+const FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+struct Chip8Tui {
+    chip: Chip,
+    random: Random,
+    screen: FrameBuffer,
+    keypad: Keypad,
+    ticks: u32,
+}
+
+impl Chip8Tui {
+    fn new(ticks: u32) -> Chip8Tui {
+        Chip8Tui {
+            chip: Chip::default(),
+            random: Random {},
+            screen: FrameBuffer::default(),
+            keypad: Keypad::new(),
+            ticks,
+        }
+    }
+
+    fn load_program(&mut self, program_path: &str) {
+        let program = fs::read(program_path).expect("Unable to load program.");
+        if program.len() > PROGRAM_SIZE {
+            eprintln!("{}: program too large, {} bytes of {}", program_path, program.len(), PROGRAM_SIZE);
+            exit(1);
+        }
+        let mut program_code: [u8; PROGRAM_SIZE] = [0; PROGRAM_SIZE];
+        for (i, data) in program.iter().enumerate() {
+            program_code[i] = *data;
+        }
+        self.chip.load_program(program_code);
+    }
+
+    // Runs one 60Hz frame, returning false when the program stops.
+    fn frame(&mut self) -> bool {
+        for _i in 0..self.ticks {
+            if !self.chip.tick(&mut self.random, &mut self.screen, &self.keypad) {
+                return false;
+            }
+        }
+        self.chip.tick_timers();
+        self.keypad.tick();
+        true
+    }
+}
+
+fn run(chip8: &mut Chip8Tui, mode: Mode, stdout: &mut impl Write) -> io::Result<()> {
+    let mut previous = None;
+    let mut deadline = Instant::now();
+
+    loop {
+        while poll(Duration::from_secs(0))? {
+            if let Event::Key(event) = read()? {
+                if event.code == KeyCode::Esc {
+                    return Ok(());
+                }
+                if let Some(keycode) = map_key(event.code) {
+                    match event.kind {
+                        KeyEventKind::Release => chip8.keypad.key_released(keycode),
+                        _ => chip8.keypad.key_pressed(keycode),
+                    }
+                }
+            }
+        }
+
+        if !chip8.frame() {
+            return Ok(());
+        }
+
+        if previous != Some(chip8.screen) {
+            for (row, line) in render(&chip8.screen, mode).iter().enumerate() {
+                queue!(stdout, MoveTo(0, row as u16), Print(line))?;
+            }
+            stdout.flush()?;
+            previous = Some(chip8.screen);
+        }
+
+        deadline += FRAME_DURATION;
+        let now = Instant::now();
+        if deadline > now {
+            std::thread::sleep(deadline - now);
+        } else {
+            deadline = now;
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let options = cli::get_options();
+
+    let chip8 = &mut Chip8Tui::new(options.ticks);
+    chip8.load_program(&options.program_path);
+
+    // Headless mode, useful for CI, running a fixed number of frames without input.
+    if let Some(frames) = options.frames {
+        for _frame in 0..frames {
+            if !chip8.frame() {
+                break;
+            }
+        }
+        for line in render(&chip8.screen, options.mode) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    let stdout = &mut io::stdout();
+    enable_raw_mode()?;
+    let enhanced = supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced {
+        execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES))?;
+    }
+    execute!(stdout, EnterAlternateScreen, Hide, Clear(ClearType::All))?;
+
+    let result = run(chip8, options.mode, stdout);
+
+    if enhanced {
+        execute!(stdout, PopKeyboardEnhancementFlags)?;
+    }
+    execute!(stdout, Show, LeaveAlternateScreen)?;
+    disable_raw_mode()?;
+
+    result
+}
@@ -0,0 +1,37 @@
+//! Fuzz target running arbitrary programs in the Chip8 VM and in the reference model of the
+//! differential tests, checking that the VM never panics and matches the model.
+//!
+//! The first byte selects the pressed keys, the memory policy, the I register width, the
+//! sprite mode, the display wait and the VIP arithmetic quirks, the next 4 bytes are the
+//! random numbers and the rest of the input is the program.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use chip8vm::quirks::{IndexRegister, MemoryPolicy, Quirks, SpriteMode};
+use chip8vm::PROGRAM_SIZE;
+
+#[path = "../../tests/differential/model.rs"]
+mod model;
+
+/// Maximum number of instructions executed by a program.
+const TICKS: usize = 5000;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 5 || data.len() - 5 > PROGRAM_SIZE {
+        return;
+    }
+
+    let keys = [None, Some(data[0] & 0xF), Some(data[0] >> 4)];
+    let quirks = Quirks {
+        memory: [MemoryPolicy::Wrap, MemoryPolicy::Clamp, MemoryPolicy::Fault][data[0] as usize % 3],
+        index: [IndexRegister::Wrap12, IndexRegister::Overflow, IndexRegister::Bits16][data[0] as usize / 3 % 3],
+        sprite: [SpriteMode::Wrap, SpriteMode::Clip, SpriteMode::SchipRows][data[0] as usize / 9 % 3],
+        display_wait: data[0] / 27 % 2 == 1,
+        vf_reset: data[0] / 54 % 2 == 1,
+        shift_vy: data[0] / 54 % 2 == 1,
+        load_store_increment: data[0] / 54 % 2 == 1,
+        ..Quirks::CHIP8
+    };
+    model::check(&data[5..], quirks, &data[1..5], &keys, TICKS);
+});
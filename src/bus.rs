@@ -0,0 +1,363 @@
+use crate::font::Font;
+use crate::quirks::Quirks;
+use crate::specs::{ADDRESS_SPACE, PROG_START};
+use crate::{Bus, MEMORY_SIZE};
+
+/// Constant defining the size of the RAM pages used by FlashMemory.
+pub const PAGE_SIZE: usize = 64;
+
+/// Number of pages of the addressable memory.
+const NUM_PAGES: usize = MEMORY_SIZE / PAGE_SIZE;
+
+/// Page table entry of the pages that are not copied to RAM.
+const UNMAPPED: u8 = u8::MAX;
+
+/// Memory structure implementing the Bus trait over an internal array of SIZE bytes. It is the
+/// default bus of the Chip8 VM.
+///
+/// Bus addresses are 16 bits wide, so SIZE can't be larger than 0x10000. Bigger memories fail to
+/// compile when they are created.
+///
+/// ```compile_fail
+/// use chip8vm::bus::Memory;
+///
+/// let memory = Memory::<0x10001>::new();
+/// ```
+#[derive(Clone)]
+pub struct Memory<const SIZE: usize = MEMORY_SIZE> {
+    data: [u8; SIZE],
+}
+
+impl<const SIZE: usize> Memory<SIZE> {
+    /// Compile time check of the size of the memory against the 16 bit address space.
+    const SIZE_CHECK: () =
+        assert!(SIZE <= ADDRESS_SPACE, "Memory SIZE exceeds the 16 bit address space");
+
+    /// Creates a new memory initialized to 0.
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::SIZE_CHECK;
+        Memory { data: [0; SIZE] }
+    }
+
+    /// Returns the memory contents.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Default implementation for Memory structure.
+///
+/// Initializes all the memory to 0.
+impl<const SIZE: usize> Default for Memory<SIZE> {
+    fn default() -> Self {
+        Memory::new()
+    }
+}
+
+impl<const SIZE: usize> Bus for Memory<SIZE> {
+    fn read(&mut self, address: u16) -> u8 {
+        self.data[address as usize]
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        self.data[address as usize] = value;
+    }
+
+    fn size(&self) -> usize {
+        SIZE
+    }
+}
+
+/// FlashMemory structure implementing the Bus trait to execute a program in place from a
+/// read-only image, like a ROM stored in the flash memory of a microcontroller.
+///
+/// Reads are served from the font of the quirks and the program image until a page is written.
+/// The first write to a page copies it to one of the PAGES RAM pages, so only modified pages
+/// consume RAM. Writes that do not change the stored value do not use a RAM page. When all the
+/// RAM pages are in use, writes to new pages are dropped and counted.
+///
+/// The font and the program are already mapped, so Chip::load_program does not need to be
+/// called. The program is mapped at START, which must match the START of the Chip, like 0x600
+/// for the Eti660Chip.
+///
+/// # Example
+///
+/// ```
+/// use chip8vm::bus::FlashMemory;
+/// use chip8vm::chip::Chip;
+///
+/// static ROM: [u8; 4] = [0x00, 0xE0, 0x12, 0x02];
+///
+/// // Program variables usually fit in 4 pages of RAM.
+/// let memory: FlashMemory<4> = FlashMemory::new(&ROM).unwrap();
+/// let chip: Chip<_> = Chip::with_bus(memory);
+/// ```
+pub struct FlashMemory<'a, const PAGES: usize, const START: usize = PROG_START> {
+    /// Program image, mapped at START.
+    rom: &'a [u8],
+
+    /// Font, mapped at the font address.
+    font: &'static Font,
+
+    /// Font address.
+    font_address: usize,
+
+    /// RAM page used by every memory page, or UNMAPPED.
+    table: [u8; NUM_PAGES],
+
+    /// RAM pages.
+    pages: [[u8; PAGE_SIZE]; PAGES],
+
+    /// Number of RAM pages in use.
+    used: usize,
+
+    /// Number of writes dropped because all the RAM pages were in use.
+    dropped: usize,
+}
+
+impl<'a, const PAGES: usize, const START: usize> FlashMemory<'a, PAGES, START> {
+    /// Creates a new memory executing the program image in place, with the Quirks::CHIP8 font.
+    ///
+    /// # Parameters
+    /// * rom - Program image.
+    ///
+    /// # Return
+    /// * None - If the program does not fit in the memory after START.
+    pub const fn new(rom: &'a [u8]) -> Option<Self> {
+        FlashMemory::with_quirks(rom, Quirks::CHIP8)
+    }
+
+    /// Creates a new memory executing the program image in place, with the font of the quirks.
+    /// The quirks should be the same used by the Chip.
+    ///
+    /// # Parameters
+    /// * rom - Program image.
+    /// * quirks - Quirks with the font and the font address.
+    ///
+    /// # Return
+    /// * None - If the program does not fit in the memory after START.
+    pub const fn with_quirks(rom: &'a [u8], quirks: Quirks) -> Option<Self> {
+        if rom.len() > MEMORY_SIZE.saturating_sub(START) {
+            return None;
+        }
+
+        // Same font address as Chip::set_quirks.
+        let mut font_address = quirks.font_address as usize;
+        if font_address + quirks.font.size() > START {
+            font_address = START.saturating_sub(quirks.font.size());
+        }
+
+        Some(FlashMemory {
+            rom,
+            font: quirks.font,
+            font_address,
+            table: [UNMAPPED; NUM_PAGES],
+            pages: [[0; PAGE_SIZE]; PAGES],
+            used: 0,
+            dropped: 0,
+        })
+    }
+
+    /// Returns the number of RAM pages in use.
+    pub fn pages_used(&self) -> usize {
+        self.used
+    }
+
+    /// Returns the number of writes dropped because all the RAM pages were in use.
+    pub fn dropped_writes(&self) -> usize {
+        self.dropped
+    }
+
+    /// Discards all the writes, releasing the RAM pages.
+    pub fn reset(&mut self) {
+        self.table = [UNMAPPED; NUM_PAGES];
+        self.used = 0;
+        self.dropped = 0;
+    }
+
+    /// Returns the value stored in the font or the program image.
+    fn image(&self, address: usize) -> u8 {
+        if address >= self.font_address && address - self.font_address < self.font.size() {
+            self.font.byte(address - self.font_address)
+        } else if address >= START && address - START < self.rom.len() {
+            self.rom[address - START]
+        } else {
+            0
+        }
+    }
+}
+
+impl<'a, const PAGES: usize, const START: usize> Bus for FlashMemory<'a, PAGES, START> {
+    fn read(&mut self, address: u16) -> u8 {
+        let address = address as usize;
+        match self.table[address / PAGE_SIZE] {
+            UNMAPPED => self.image(address),
+            page => self.pages[page as usize][address % PAGE_SIZE],
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u8) {
+        let address = address as usize;
+        let page = address / PAGE_SIZE;
+
+        if self.table[page] == UNMAPPED {
+            if self.image(address) == value {
+                return;
+            }
+            if self.used == PAGES {
+                self.dropped += 1;
+                return;
+            }
+
+            let start = page * PAGE_SIZE;
+            for offset in 0..PAGE_SIZE {
+                self.pages[self.used][offset] = self.image(start + offset);
+            }
+            self.table[page] = self.used as u8;
+            self.used += 1;
+        }
+
+        self.pages[self.table[page] as usize][address % PAGE_SIZE] = value;
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::Chip;
+    use crate::framebuffer::FrameBuffer;
+    use crate::{Keypad, Random};
+
+    struct NoRandom;
+
+    impl Random for NoRandom {
+        fn range(&mut self) -> u8 { 0 }
+    }
+
+    struct NoKeypad;
+
+    impl Keypad for NoKeypad {
+        fn is_pressed(&self, _keycode: u8) -> bool { false }
+        fn pressed_key(&self) -> Option<u8> { None }
+    }
+
+    #[test]
+    fn read_write() {
+        let mut memory: Memory = Memory::default();
+
+        memory.write(0x000, 0x12);
+        memory.write(0xFFF, 0x34);
+
+        assert_eq!(memory.read(0x000), 0x12);
+        assert_eq!(memory.read(0xFFF), 0x34);
+        assert_eq!(memory.data()[0xFFF], 0x34);
+        assert_eq!(memory.data().len(), MEMORY_SIZE);
+        assert_eq!(memory.size(), MEMORY_SIZE);
+    }
+
+    #[test]
+    fn memory_size() {
+        let mut memory: Memory<0x10000> = Memory::default();
+
+        memory.write(0xFFFF, 0x12);
+
+        assert_eq!(memory.read(0xFFFF), 0x12);
+        assert_eq!(memory.size(), 0x10000);
+    }
+
+    #[test]
+    fn flash_reads_image() {
+        let rom = [0x12, 0x34];
+        let mut memory: FlashMemory<1> = FlashMemory::new(&rom).unwrap();
+
+        assert_eq!(memory.read(0x000), Font::CHIP8.set[0]);
+        assert_eq!(memory.read(0x04F), Font::CHIP8.set[0x4F]);
+        assert_eq!(memory.read(0x050), 0);
+        assert_eq!(memory.read(0x200), 0x12);
+        assert_eq!(memory.read(0x201), 0x34);
+        assert_eq!(memory.read(0x202), 0);
+        assert!(FlashMemory::<1>::new(&[0; MEMORY_SIZE - PROG_START + 1]).is_none());
+        assert!(FlashMemory::<1, 0x600>::new(&[0; MEMORY_SIZE - 0x600 + 1]).is_none());
+
+        let quirks = Quirks {
+            font_address: 0x050,
+            ..Quirks::DREAM6800
+        };
+        let mut memory: FlashMemory<1> = FlashMemory::with_quirks(&rom, quirks).unwrap();
+        assert_eq!(memory.read(0x000), 0);
+        assert_eq!(memory.read(0x050), Font::DREAM6800.set[0]);
+        assert_eq!(memory.read(0x09F), Font::DREAM6800.set[0x4F]);
+        assert_eq!(memory.read(0x0A0), 0);
+    }
+
+    #[test]
+    fn flash_copy_on_write() {
+        let rom = [0x12, 0x34];
+        let mut memory: FlashMemory<2> = FlashMemory::new(&rom).unwrap();
+
+        memory.write(0x200, 0x12);
+        assert_eq!(memory.pages_used(), 0);
+
+        memory.write(0x202, 0x56);
+        memory.write(0x23F, 0x78);
+        assert_eq!(memory.pages_used(), 1);
+        assert_eq!(memory.read(0x200), 0x12);
+        assert_eq!(memory.read(0x201), 0x34);
+        assert_eq!(memory.read(0x202), 0x56);
+        assert_eq!(memory.read(0x23F), 0x78);
+
+        memory.write(0xF00, 0x01);
+        memory.write(0x300, 0x02);
+        assert_eq!(memory.pages_used(), 2);
+        assert_eq!(memory.dropped_writes(), 1);
+        assert_eq!(memory.read(0xF00), 0x01);
+        assert_eq!(memory.read(0x300), 0);
+
+        memory.reset();
+        assert_eq!(memory.pages_used(), 0);
+        assert_eq!(memory.read(0x202), 0);
+    }
+
+    #[test]
+    fn flash_execute_in_place() {
+        // LD V0, 123; LD I, 0x300; LD B, V0; DRW V1, V1, 3
+        static ROM: [u8; 8] = [0x60, 0x7B, 0xA3, 0x00, 0xF0, 0x33, 0xD1, 0x13];
+        let mut chip: Chip<_> = Chip::with_bus(FlashMemory::<1>::new(&ROM).unwrap());
+        let mut screen = FrameBuffer::default();
+
+        for _tick in 0..4 {
+            assert!(chip.tick(&mut NoRandom, &mut screen, &NoKeypad));
+        }
+
+        assert_eq!(chip.bus_mut().read(0x300), 1);
+        assert_eq!(chip.bus_mut().read(0x301), 2);
+        assert_eq!(chip.bus_mut().read(0x302), 3);
+        assert_eq!(chip.bus().pages_used(), 1);
+        assert!(screen.is_on(7, 0));
+        assert!(screen.is_on(6, 1));
+        assert!(!screen.is_on(7, 1));
+    }
+
+    #[test]
+    fn flash_eti660_start() {
+        // LD V0, 123; LD I, 0x700; LD B, V0
+        static ROM: [u8; 6] = [0x60, 0x7B, 0xA7, 0x00, 0xF0, 0x33];
+        let memory = FlashMemory::<1, 0x600>::new(&ROM).unwrap();
+        let mut chip: Chip<_, 16, 0x600> = Chip::with_bus(memory);
+        let mut screen = FrameBuffer::default();
+
+        for _tick in 0..3 {
+            assert!(chip.tick(&mut NoRandom, &mut screen, &NoKeypad));
+        }
+
+        assert_eq!(chip.bus_mut().read(0x200), 0);
+        assert_eq!(chip.bus_mut().read(0x600), 0x60);
+        assert_eq!(chip.bus_mut().read(0x700), 1);
+        assert_eq!(chip.bus_mut().read(0x701), 2);
+        assert_eq!(chip.bus_mut().read(0x702), 3);
+    }
+}
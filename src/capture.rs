@@ -0,0 +1,572 @@
+use crate::framebuffer::FrameBuffer;
+use crate::palette::{Palette, PALETTE_SIZE};
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Frequency in Hz of the frames provided to the GifRecorder.
+pub const FRAME_RATE: u32 = 60;
+
+/// Maximum scale of the PNG images and GIF recordings.
+pub const MAX_SCALE: usize = 64;
+
+/// Writes the framebuffer as a binary PBM (P4) image. Pixels that are on are written as black.
+///
+/// # Parameters
+/// * framebuffer - Framebuffer to capture.
+/// * output - Output function receiving the encoded data.
+pub fn write_pbm(framebuffer: &FrameBuffer, output: &mut impl FnMut(&[u8])) {
+    let mut header = Header::default();
+    header.text(b"P4\n");
+    header.number(SCREEN_WIDTH);
+    header.text(b" ");
+    header.number(SCREEN_HEIGHT);
+    header.text(b"\n");
+
+    output(header.data());
+    output(framebuffer.data());
+}
+
+/// Writes the framebuffer as an indexed PNG image using the provided palette.
+///
+/// # Parameters
+/// * framebuffer - Framebuffer to capture.
+/// * palette - Palette used to color the pixels.
+/// * scale - Size in pixels of every Chip8 VM pixel, from 1 to MAX_SCALE.
+/// * output - Output function receiving the encoded data.
+///
+/// # Return
+/// False, without writing anything, if the scale is out of range.
+pub fn write_png(framebuffer: &FrameBuffer, palette: &Palette, scale: usize, output: &mut impl FnMut(&[u8])) -> bool {
+    if !(1..=MAX_SCALE).contains(&scale) {
+        return false;
+    }
+
+    let width = SCREEN_WIDTH * scale;
+    let height = SCREEN_HEIGHT * scale;
+
+    output(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+
+    let mut chunk = Chunk::start(output, b"IHDR", 13);
+    chunk.write(&(width as u32).to_be_bytes());
+    chunk.write(&(height as u32).to_be_bytes());
+    // Bit depth 8, indexed color, default compression, filter and no interlace.
+    chunk.write(&[8, 3, 0, 0, 0]);
+    chunk.end();
+
+    let mut chunk = Chunk::start(output, b"PLTE", (PALETTE_SIZE * 3) as u32);
+    for color in palette.colors.iter() {
+        chunk.write(color);
+    }
+    chunk.end();
+
+    // Image data is stored in uncompressed deflate blocks inside a zlib stream.
+    let raw_size = height * (width + 1);
+    let blocks = (raw_size + STORED_BLOCK_SIZE - 1) / STORED_BLOCK_SIZE;
+    let mut chunk = Chunk::start(output, b"IDAT", (2 + blocks * 5 + raw_size + 4) as u32);
+    chunk.write(&[0x78, 0x01]);
+    let mut deflate = Stored::new(raw_size);
+    for y in 0..height {
+        deflate.write(&mut chunk, 0);
+        for x in 0..width {
+            let index = framebuffer.is_on(x / scale, y / scale) as u8;
+            deflate.write(&mut chunk, index);
+        }
+    }
+    let adler = deflate.adler();
+    chunk.write(&adler.to_be_bytes());
+    chunk.end();
+
+    Chunk::start(output, b"IEND", 0).end();
+    true
+}
+
+/// Animated GIF recorder.
+///
+/// Frames must be provided at FRAME_RATE and the delays of the GIF frames are adjusted so the
+/// animation keeps the same cadence, merging consecutive identical frames.
+///
+/// # Example
+///
+/// ```
+/// use chip8vm::capture::GifRecorder;
+/// use chip8vm::framebuffer::FrameBuffer;
+/// use chip8vm::palette::Palette;
+///
+/// let mut gif = Vec::new();
+/// let mut output = |data: &[u8]| gif.extend_from_slice(data);
+///
+/// let mut recorder = GifRecorder::start(Palette::default(), 4, &mut output).unwrap();
+/// recorder.frame(&FrameBuffer::default(), &mut output);
+/// recorder.finish(&mut output);
+/// ```
+pub struct GifRecorder {
+    /// Palette used to color the pixels.
+    palette: Palette,
+
+    /// Size in pixels of every Chip8 VM pixel.
+    scale: usize,
+
+    /// Last provided frame, not yet written.
+    pending: Option<FrameBuffer>,
+
+    /// Number of frames the pending frame has been displayed.
+    pending_frames: u32,
+
+    /// Number of frames already written, measured at FRAME_RATE.
+    written_frames: u32,
+
+    /// Total delay already written, in hundredths of second.
+    written_delay: u32,
+}
+
+impl GifRecorder {
+    /// Starts a new recording, writing the GIF header.
+    ///
+    /// # Parameters
+    /// * palette - Palette used to color the pixels.
+    /// * scale - Size in pixels of every Chip8 VM pixel, from 1 to MAX_SCALE.
+    /// * output - Output function receiving the encoded data.
+    ///
+    /// # Return
+    /// None, without writing anything, if the scale is out of range.
+    pub fn start(palette: Palette, scale: usize, output: &mut impl FnMut(&[u8])) -> Option<Self> {
+        if !(1..=MAX_SCALE).contains(&scale) {
+            return None;
+        }
+
+        let width = (SCREEN_WIDTH * scale) as u16;
+        let height = (SCREEN_HEIGHT * scale) as u16;
+
+        output(b"GIF89a");
+        output(&width.to_le_bytes());
+        output(&height.to_le_bytes());
+        // Global color table with 4 colors, background color 0 and square pixels.
+        output(&[0x91, 0x00, 0x00]);
+        for color in palette.colors.iter() {
+            output(color);
+        }
+        // Loop the animation forever.
+        output(&[0x21, 0xFF, 0x0B]);
+        output(b"NETSCAPE2.0");
+        output(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+
+        Some(GifRecorder {
+            palette,
+            scale,
+            pending: None,
+            pending_frames: 0,
+            written_frames: 0,
+            written_delay: 0,
+        })
+    }
+
+    /// Returns the palette used by the recording.
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    /// Adds a frame to the recording.
+    ///
+    /// # Parameters
+    /// * framebuffer - Current content of the screen.
+    /// * output - Output function receiving the encoded data.
+    pub fn frame(&mut self, framebuffer: &FrameBuffer, output: &mut impl FnMut(&[u8])) {
+        if self.pending.as_ref() == Some(framebuffer) {
+            self.pending_frames += 1;
+            return;
+        }
+
+        self.flush(output);
+        self.pending = Some(*framebuffer);
+        self.pending_frames = 1;
+    }
+
+    /// Finishes the recording, writing the pending frame and the GIF trailer.
+    ///
+    /// # Parameters
+    /// * output - Output function receiving the encoded data.
+    pub fn finish(mut self, output: &mut impl FnMut(&[u8])) {
+        self.flush(output);
+        output(&[0x3B]);
+    }
+
+    fn flush(&mut self, output: &mut impl FnMut(&[u8])) {
+        let frame = match self.pending.take() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        self.written_frames += self.pending_frames;
+        let total_delay = (self.written_frames * 100 + FRAME_RATE / 2) / FRAME_RATE;
+        let delay = (total_delay - self.written_delay) as u16;
+        self.written_delay = total_delay;
+
+        let width = (SCREEN_WIDTH * self.scale) as u16;
+        let height = (SCREEN_HEIGHT * self.scale) as u16;
+
+        // Graphic control extension with the frame delay.
+        output(&[0x21, 0xF9, 0x04, 0x00]);
+        output(&delay.to_le_bytes());
+        output(&[0x00, 0x00]);
+
+        // Image descriptor covering the whole screen.
+        output(&[0x2C, 0x00, 0x00, 0x00, 0x00]);
+        output(&width.to_le_bytes());
+        output(&height.to_le_bytes());
+        output(&[0x00]);
+
+        let scale = self.scale;
+        let pixels = (0..height as usize).flat_map(|y| {
+            (0..width as usize).map(move |x| frame.is_on(x / scale, y / scale) as u8)
+        });
+        lzw_encode(pixels, output);
+    }
+}
+
+/// Maximum size of the data of a stored deflate block.
+const STORED_BLOCK_SIZE: usize = 0xFFFF;
+
+/// Small buffer to compose text headers.
+#[derive(Default)]
+struct Header {
+    data: [u8; 32],
+    length: usize,
+}
+
+impl Header {
+    fn text(&mut self, text: &[u8]) {
+        self.data[self.length..(self.length + text.len())].copy_from_slice(text);
+        self.length += text.len();
+    }
+
+    fn number(&mut self, number: usize) {
+        let mut digits = [0; 20];
+        let mut count = 0;
+        let mut number = number;
+        loop {
+            digits[count] = b'0' + (number % 10) as u8;
+            count += 1;
+            number /= 10;
+            if number == 0 {
+                break;
+            }
+        }
+        digits[..count].reverse();
+        self.text(&digits[..count]);
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.data[..self.length]
+    }
+}
+
+/// PNG chunk writer, computing the chunk CRC while the data is written.
+struct Chunk<'a, F: FnMut(&[u8])> {
+    output: &'a mut F,
+    crc: u32,
+}
+
+impl<'a, F: FnMut(&[u8])> Chunk<'a, F> {
+    fn start(output: &'a mut F, kind: &[u8; 4], length: u32) -> Self {
+        output(&length.to_be_bytes());
+        let mut chunk = Chunk { output, crc: 0xFFFF_FFFF };
+        chunk.write(kind);
+        chunk
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        for byte in data {
+            self.crc ^= *byte as u32;
+            for _ in 0..8 {
+                self.crc = if self.crc & 1 == 1 { (self.crc >> 1) ^ 0xEDB8_8320 } else { self.crc >> 1 };
+            }
+        }
+        (self.output)(data);
+    }
+
+    fn end(self) {
+        (self.output)(&(!self.crc).to_be_bytes());
+    }
+}
+
+/// Deflate stream of stored blocks, computing the zlib Adler-32 checksum.
+struct Stored {
+    remaining: usize,
+    block_remaining: usize,
+    a: u32,
+    b: u32,
+}
+
+impl Stored {
+    fn new(size: usize) -> Self {
+        Stored {
+            remaining: size,
+            block_remaining: 0,
+            a: 1,
+            b: 0,
+        }
+    }
+
+    fn write<F: FnMut(&[u8])>(&mut self, chunk: &mut Chunk<F>, byte: u8) {
+        if self.block_remaining == 0 {
+            let length = self.remaining.min(STORED_BLOCK_SIZE);
+            let last = (length == self.remaining) as u8;
+            chunk.write(&[last]);
+            chunk.write(&(length as u16).to_le_bytes());
+            chunk.write(&(!(length as u16)).to_le_bytes());
+            self.block_remaining = length;
+        }
+
+        chunk.write(&[byte]);
+        self.block_remaining -= 1;
+        self.remaining -= 1;
+        self.a = (self.a + byte as u32) % 65521;
+        self.b = (self.b + self.a) % 65521;
+    }
+
+    fn adler(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+}
+
+/// Minimum LZW code size for a 4 colors image.
+const LZW_MIN_CODE_SIZE: u8 = 2;
+const LZW_CLEAR: u16 = 1 << LZW_MIN_CODE_SIZE;
+const LZW_END: u16 = LZW_CLEAR + 1;
+const LZW_MAX_CODES: usize = 4096;
+
+/// Encodes the pixels as GIF LZW image data, including the data sub-blocks.
+fn lzw_encode(pixels: impl Iterator<Item = u8>, output: &mut impl FnMut(&[u8])) {
+    // Code table indexed by prefix code and pixel value. Zero means not present.
+    let mut table = [[0u16; PALETTE_SIZE]; LZW_MAX_CODES];
+    let mut next = LZW_END + 1;
+    let mut code_size = LZW_MIN_CODE_SIZE + 1;
+    let mut prefix: Option<u16> = None;
+
+    output(&[LZW_MIN_CODE_SIZE]);
+    let mut bits = BitWriter::default();
+    bits.write(LZW_CLEAR, code_size, output);
+
+    for pixel in pixels {
+        let current = match prefix {
+            None => {
+                prefix = Some(pixel as u16);
+                continue;
+            }
+            Some(current) => current,
+        };
+
+        let code = table[current as usize][pixel as usize];
+        if code != 0 {
+            prefix = Some(code);
+            continue;
+        }
+
+        bits.write(current, code_size, output);
+        if (next as usize) < LZW_MAX_CODES {
+            table[current as usize][pixel as usize] = next;
+            next += 1;
+            if next > (1 << code_size) && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            bits.write(LZW_CLEAR, code_size, output);
+            table = [[0; PALETTE_SIZE]; LZW_MAX_CODES];
+            next = LZW_END + 1;
+            code_size = LZW_MIN_CODE_SIZE + 1;
+        }
+        prefix = Some(pixel as u16);
+    }
+
+    if let Some(current) = prefix {
+        bits.write(current, code_size, output);
+    }
+    bits.write(LZW_END, code_size, output);
+    bits.finish(output);
+}
+
+/// Packs LZW codes least significant bit first into GIF data sub-blocks.
+struct BitWriter {
+    accumulator: u32,
+    count: u8,
+    block: [u8; 255],
+    length: usize,
+}
+
+impl Default for BitWriter {
+    fn default() -> Self {
+        BitWriter {
+            accumulator: 0,
+            count: 0,
+            block: [0; 255],
+            length: 0,
+        }
+    }
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8, output: &mut impl FnMut(&[u8])) {
+        self.accumulator |= (code as u32) << self.count;
+        self.count += size;
+        while self.count >= 8 {
+            self.push(self.accumulator as u8, output);
+            self.accumulator >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn push(&mut self, byte: u8, output: &mut impl FnMut(&[u8])) {
+        self.block[self.length] = byte;
+        self.length += 1;
+        if self.length == self.block.len() {
+            self.flush(output);
+        }
+    }
+
+    fn flush(&mut self, output: &mut impl FnMut(&[u8])) {
+        if self.length > 0 {
+            output(&[self.length as u8]);
+            output(&self.block[..self.length]);
+            self.length = 0;
+        }
+    }
+
+    fn finish(mut self, output: &mut impl FnMut(&[u8])) {
+        if self.count > 0 {
+            self.push(self.accumulator as u8, output);
+        }
+        self.flush(output);
+        output(&[0x00]);
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::Screen;
+    use std::vec::Vec;
+
+    fn test_frame() -> FrameBuffer {
+        let mut framebuffer = FrameBuffer::default();
+        for i in 0..SCREEN_HEIGHT {
+            framebuffer.draw(i as u8 * 2, i as u8);
+            framebuffer.draw(63, i as u8);
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn pbm() {
+        let framebuffer = test_frame();
+        let mut pbm = Vec::new();
+
+        write_pbm(&framebuffer, &mut |data: &[u8]| pbm.extend_from_slice(data));
+
+        assert_eq!(&pbm[..9], b"P4\n64 32\n");
+        assert_eq!(&pbm[9..], &framebuffer.data()[..]);
+    }
+
+    #[test]
+    fn png() {
+        let framebuffer = test_frame();
+        let mut png = Vec::new();
+
+        write_png(&framebuffer, &Palette::OCTO, 3, &mut |data: &[u8]| png.extend_from_slice(data));
+
+        let decoder = ::png::Decoder::new(std::io::Cursor::new(png));
+        let mut reader = decoder.read_info().unwrap();
+        let mut image = std::vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut image).unwrap();
+        assert_eq!((info.width, info.height), (64 * 3, 32 * 3));
+        for y in 0..info.height as usize {
+            for x in 0..info.width as usize {
+                let expected = framebuffer.is_on(x / 3, y / 3) as u8;
+                assert_eq!(image[y * info.width as usize + x], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn gif() {
+        let first = test_frame();
+        let mut second = first;
+        second.clear();
+        let mut gif = Vec::new();
+        let mut output = |data: &[u8]| gif.extend_from_slice(data);
+
+        let mut recorder = GifRecorder::start(Palette::AMBER, 2, &mut output).unwrap();
+        recorder.frame(&first, &mut output);
+        recorder.frame(&first, &mut output);
+        recorder.frame(&first, &mut output);
+        recorder.frame(&second, &mut output);
+        recorder.finish(&mut output);
+
+        let mut options = ::gif::DecodeOptions::new();
+        options.set_color_output(::gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(std::io::Cursor::new(gif)).unwrap();
+
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(frame.delay, 5);
+        for y in 0..64 {
+            for x in 0..128 {
+                assert_eq!(frame.buffer[y * 128 + x], first.is_on(x / 2, y / 2) as u8);
+            }
+        }
+
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(frame.delay, 2);
+        assert!(frame.buffer.iter().all(|pixel| *pixel == 0));
+
+        assert!(decoder.read_next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn gif_large_image() {
+        let mut framebuffer = FrameBuffer::default();
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                framebuffer.set(x, y, (x * 7 + y * 13) % 5 < 2);
+            }
+        }
+        let mut gif = Vec::new();
+        let mut output = |data: &[u8]| gif.extend_from_slice(data);
+
+        let mut recorder = GifRecorder::start(Palette::default(), 8, &mut output).unwrap();
+        recorder.frame(&framebuffer, &mut output);
+        recorder.finish(&mut output);
+
+        let mut options = ::gif::DecodeOptions::new();
+        options.set_color_output(::gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(std::io::Cursor::new(gif)).unwrap();
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        for y in 0..256 {
+            for x in 0..512 {
+                assert_eq!(frame.buffer[y * 512 + x], framebuffer.is_on(x / 8, y / 8) as u8);
+            }
+        }
+    }
+
+    #[test]
+    fn scale_out_of_range() {
+        let framebuffer = FrameBuffer::default();
+        let mut written = false;
+        let mut output = |_bytes: &[u8]| written = true;
+
+        assert!(!write_png(&framebuffer, &Palette::default(), 0, &mut output));
+        assert!(!write_png(&framebuffer, &Palette::default(), MAX_SCALE + 1, &mut output));
+        assert!(GifRecorder::start(Palette::default(), 0, &mut output).is_none());
+        assert!(GifRecorder::start(Palette::default(), MAX_SCALE + 1, &mut output).is_none());
+        assert!(!written);
+
+        let mut data = Vec::new();
+        let mut output = |bytes: &[u8]| data.extend_from_slice(bytes);
+        let mut recorder = GifRecorder::start(Palette::default(), MAX_SCALE, &mut output).unwrap();
+        recorder.frame(&framebuffer, &mut output);
+        recorder.finish(&mut output);
+        assert!(write_png(&framebuffer, &Palette::default(), MAX_SCALE, &mut output));
+    }
+}
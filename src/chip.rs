@@ -1,33 +1,94 @@
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::bus::Memory;
 use crate::font::*;
+use crate::quirks::{IndexRegister, MemoryPolicy, Quirks, SpriteMode};
 use crate::specs::*;
-use crate::{Random, Screen, Keypad, SCREEN_WIDTH, SCREEN_HEIGHT, PROGRAM_SIZE};
+use crate::{Bus, Random, Screen, Keypad, SCREEN_WIDTH, SCREEN_HEIGHT, PROGRAM_SIZE};
 
 /// Chip structure that contains the needed state for the Chip8 VM to work.
 ///
-/// Sizes of the fields are defined in the specs.rs module.
-pub struct Chip {
+/// Memory is accessed through the Bus provided on creation, by default an internal
+/// bus::Memory array. The stack depth is defined by STACK and programs are loaded and start
+/// execution at the START address. Default sizes are defined in the specs.rs module, and the
+/// ClassicChip, Eti660Chip, SchipChip and XoChip aliases define the sizes of common
+/// platforms.
+pub struct Chip<B: Bus = Memory, const STACK: usize = STACK_SIZE, const START: usize = PROG_START> {
     /// Set of registers for the Chip8 VM.
     registers: [u8; NUM_RESGISTERS],
 
     /// Address register.
     i: u16,
 
-    /// Available memory for the Chip8 VM.
-    memory: [u8; MEM_SIZE],
+    /// Memory bus of the Chip8 VM.
+    bus: B,
 
     /// Instruction pointer.
     ip: usize,
 
     /// Available memory for the stack of the Chip8 VM.
-    stack: [u16; STACK_SIZE],
+    stack: [u16; STACK],
 
     /// Stack pointer.
     sp: usize,
 
     /// Available timers fot the Chip8 VM.
     timers: [u8; NUM_TIMERS],
+
+    /// Behaviours that differ between interpreters.
+    quirks: Quirks,
+
+    /// Fault that stopped the last tick.
+    fault: Option<Fault>,
+
+    /// True if a sprite was drawn in the current frame, for the display wait quirk.
+    drawn: bool,
+}
+
+/// Memory scrub applied by Chip::reload before loading the program.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Scrub {
+    /// Memory is kept as it is.
+    None,
+
+    /// Memory is filled with zeros.
+    Zeros,
+
+    /// Memory is filled with random values, like the RAM of real hardware at power on.
+    Random,
+}
+
+/// Fault stopping the execution of the Chip8 VM, returned by Chip::fault when a tick returns
+/// false.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Fault {
+    /// The opcode is not a valid instruction. Programs use the 0x0000 opcode to stop.
+    Instruction(u16),
+
+    /// Return from a subroutine with an empty stack.
+    StackUnderflow,
+
+    /// Subroutine call with a full stack.
+    StackOverflow,
+
+    /// Memory access or instruction fetch beyond the end of the memory, with the
+    /// MemoryPolicy::Fault policy.
+    Address(usize),
 }
 
+/// Classic COSMAC VIP Chip8 VM: 4 KiB of memory, 16 stack levels and programs at 0x200.
+pub type ClassicChip = Chip<Memory<0x1000>, 16, 0x200>;
+
+/// ETI-660 Chip8 VM: 4 KiB of memory, 16 stack levels and programs at 0x600.
+pub type Eti660Chip = Chip<Memory<0x1000>, 16, 0x600>;
+
+/// HP48 SCHIP VM: 4 KiB of memory, 16 stack levels and programs at 0x200.
+pub type SchipChip = Chip<Memory<0x1000>, 16, 0x200>;
+
+/// XO-CHIP VM: 64 KiB of memory, 16 stack levels and programs at 0x200.
+pub type XoChip = Chip<Memory<0x10000>, 16, 0x200>;
+
 /// Default implementation for Chip structure.
 ///
 /// Initializes a Chip structure with default values and sizes defined in the specs.rs module.
@@ -35,25 +96,105 @@ impl Default for Chip {
 
     /// default function for fields initialization.
     fn default() -> Self {
+        Chip::new()
+    }
+}
+
+/// The Chip implementation contains the required functions to decode the Chip8 opcodes and
+/// perform it's associated actions.
+impl<B: Bus, const STACK: usize, const START: usize> Chip<B, STACK, START> {
+    /// Creates a Chip structure with default values, accessing memory through the provided bus.
+    ///
+    /// The stack depth and the program start are taken from the Chip type, so `Chip<_>` selects
+    /// the default ones.
+    ///
+    /// # Parameters
+    /// * bus - Memory bus used for all memory accesses.
+    pub const fn with_bus(bus: B) -> Self {
         Chip {
             registers: [0; NUM_RESGISTERS],
             i: 0,
-            memory: [0; MEM_SIZE],
-            ip: PROG_START,
-            stack: [0; STACK_SIZE],
+            bus,
+            ip: START,
+            stack: [0; STACK],
             sp: 0,
             timers: [0; NUM_TIMERS],
+            quirks: Quirks::CHIP8,
+            fault: None,
+            drawn: false,
         }
     }
-}
 
-/// The Chip implementation contains the required functions to decode the Chip8 opcodes and
-/// perform it's associated actions.
-impl Chip {
-    /// Loads the provided program array in the chip memory, starting at PROG_START offset.
+    /// Returns the quirks of the Chip8 VM.
+    pub fn quirks(&self) -> &Quirks {
+        &self.quirks
+    }
+
+    /// Changes the quirks of the Chip8 VM. By default Quirks::CHIP8.
+    ///
+    /// The font is written to memory when the program is loaded, so the quirks should be set
+    /// before loading it. Font addresses overlapping the program are clamped, so the font ends
+    /// at START.
+    ///
+    /// # Parameters
+    /// * quirks - New quirks.
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+        if self.quirks.font_address as usize + self.quirks.font.size() > START {
+            self.quirks.font_address = START.saturating_sub(self.quirks.font.size()) as u16;
+        }
+    }
+
+    /// Returns the memory bus.
+    pub fn bus(&self) -> &B {
+        &self.bus
+    }
+
+    /// Returns the memory bus as mutable, so the host can access devices or memory regions.
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    /// Returns the fault that stopped the last tick, if it returned false.
+    pub fn fault(&self) -> Option<Fault> {
+        self.fault
+    }
+
+    /// Returns the V0 to VF registers.
+    pub fn registers(&self) -> &[u8; NUM_RESGISTERS] {
+        &self.registers
+    }
+
+    /// Returns the address register I.
+    pub fn i(&self) -> u16 {
+        self.i
+    }
+
+    /// Returns the instruction pointer.
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// Returns the return addresses stored in the stack, the last one being the top.
+    pub fn stack(&self) -> &[u16] {
+        &self.stack[..self.sp]
+    }
+
+    /// Returns the value of the delay timer.
+    pub fn delay_timer(&self) -> u8 {
+        self.timers[DELAY_TIMER]
+    }
+
+    /// Returns the value of the sound timer.
+    pub fn sound_timer(&self) -> u8 {
+        self.timers[SOUND_TIMER]
+    }
+
+    /// Loads the provided program array in the chip memory, starting at START offset. Program
+    /// bytes beyond the end of the memory are not loaded.
     ///
-    /// It also initialize the reserved memory for the font, with the font set defined in the Font
-    /// struct.
+    /// It also initialize the reserved memory for the font, with the font and the font address
+    /// defined in the quirks.
     ///
     /// A program should be loaded before execution of the VM starts.
     ///
@@ -80,10 +221,101 @@ impl Chip {
     /// ```
     pub fn load_program(&mut self, program: [u8; PROGRAM_SIZE]) {
         // Load font
-        self.memory[..(CHARACTERS * CHARACTER_SIZE)].copy_from_slice(&Font::default().set);
+        self.load_font();
 
         // Load program
-        self.memory[PROG_START..(PROG_START + program.len())].copy_from_slice(&program);
+        let available = self.size().saturating_sub(START);
+        for (offset, data) in program.iter().take(available).enumerate() {
+            self.bus.write((START + offset) as u16, *data);
+        }
+    }
+
+    /// Loads the provided ROM in the chip memory, starting at START offset, together with the
+    /// font. Unlike load_program, the ROM can use all the memory after START.
+    ///
+    /// # Parameters
+    /// * rom - Program data.
+    ///
+    /// # Return
+    /// * false - If the ROM does not fit in memory. Nothing is loaded.
+    pub fn load_rom(&mut self, rom: &[u8]) -> bool {
+        if START + rom.len() > self.size() {
+            return false;
+        }
+
+        self.load_font();
+        for (offset, data) in rom.iter().enumerate() {
+            self.bus.write((START + offset) as u16, *data);
+        }
+        true
+    }
+
+    /// Resets the Chip8 VM to its power on state keeping the memory, so the loaded program runs
+    /// again from the start.
+    ///
+    /// Registers, stack and timers are cleared and the instruction pointer is set to START.
+    /// The screen is not owned by the VM, so it should be cleared by the caller.
+    pub fn reset(&mut self) {
+        self.registers = [0; NUM_RESGISTERS];
+        self.i = 0;
+        self.ip = START;
+        self.stack = [0; STACK];
+        self.sp = 0;
+        self.timers = [0; NUM_TIMERS];
+        self.fault = None;
+        self.drawn = false;
+    }
+
+    /// Resets the Chip8 VM, scrubs its memory and loads the provided ROM together with the font,
+    /// like a machine being powered off and on again.
+    ///
+    /// # Parameters
+    /// * rom - Program data.
+    /// * scrub - Memory contents before loading the ROM.
+    /// * random - Random numbers used by Scrub::Random.
+    ///
+    /// # Return
+    /// * false - If the ROM does not fit in memory. Nothing is changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chip8vm::chip::{Chip, Scrub};
+    /// # struct Random;
+    /// # impl chip8vm::Random for Random {
+    /// #     fn range(&mut self) -> u8 { 0x55 }
+    /// # }
+    ///
+    /// let rom = [0x12, 0x00];
+    /// let mut chip = Chip::default();
+    /// chip.load_rom(&rom);
+    ///
+    /// //... Run the program until the reset button is pressed.
+    ///
+    /// chip.reload(&rom, Scrub::Random, &mut Random);
+    /// ```
+    pub fn reload(&mut self, rom: &[u8], scrub: Scrub, random: &mut impl Random) -> bool {
+        if START + rom.len() > self.size() {
+            return false;
+        }
+
+        self.reset();
+        for address in 0..self.size() {
+            match scrub {
+                Scrub::None => break,
+                Scrub::Zeros => self.bus.write(address as u16, 0),
+                Scrub::Random => self.bus.write(address as u16, random.range()),
+            }
+        }
+        self.load_rom(rom)
+    }
+
+    /// Writes the font of the quirks at the font address.
+    fn load_font(&mut self) {
+        let font = self.quirks.font;
+        for offset in 0..font.size() {
+            self.write(self.quirks.font_address as usize + offset, font.byte(offset));
+        }
     }
 
     /// Decreases the value of the Chip8 VM timers by 1.
@@ -91,10 +323,14 @@ impl Chip {
     /// The two internal timers:
     /// * Delay timer - Used for synchronization purposes in programs.
     /// * Sound timer - Used for producing sound. Whenever the value is greater that 0 sound is
-    /// produced.
+    ///   produced.
+    ///
+    /// It also starts a new frame for the display wait quirk.
     ///
     /// This method must be called at a 60Hz frequency.
     pub fn tick_timers(&mut self) {
+        self.drawn = false;
+
         if self.timers[DELAY_TIMER] > 0 {
             self.timers[DELAY_TIMER] -= 1;
         }
@@ -112,10 +348,21 @@ impl Chip {
     /// * screen -
     /// * keypad -
     ///
+    /// # Return
+    /// * false - If the execution stops. The reason is returned by fault.
+    ///
     /// This method should be called at a frequency around 600Hz.
     pub fn tick(&mut self, random: &mut impl Random, screen: &mut impl Screen, keypad: &impl Keypad) -> bool {
-        let op_high = self.memory[self.ip] as u16;
-        let op_low = self.memory[self.ip + 1] as u16;
+        self.fault = None;
+        self.ip = match self.address(self.ip) {
+            Some(address) => address as usize,
+            None => return false,
+        };
+        let op_high = self.read(self.ip) as u16;
+        let op_low = self.read(self.ip + 1) as u16;
+        if self.fault.is_some() {
+            return false;
+        }
         let opcode = op_high << 8 | op_low;
 
         let x = ((opcode & 0x0F00) >> 8) as u8;
@@ -134,7 +381,7 @@ impl Chip {
             0x2000..=0x2FFF => self.call(address),
             0x3000..=0x3FFF => self.se_vx_byte(x, op_low as u8),
             0x4000..=0x4FFF => self.sne_vx_byte(x, op_low as u8),
-            0x5000..=0x5FFF => self.sne_vx_vy(x, y),
+            0x5000..=0x5FFF => self.se_vx_vy(x, y),
             0x6000..=0x6FFF => self.ld_vx_byte(x, op_low as u8),
             0x7000..=0x7FFF => self.add_vx_byte(x, op_low as u8),
             0x8000..=0x8FFF => match option {
@@ -144,9 +391,15 @@ impl Chip {
                 0x3 => self.xor_vx_vy(x, y),
                 0x4 => self.add_vx_vy(x, y),
                 0x5 => self.sub_vx_vy(x, y),
-                0x6 => self.shr_vx(x),
+                0x6 => {
+                    self.ld_shift_vy(x, y);
+                    self.shr_vx(x);
+                }
                 0x7 => self.subn_vx_vy(x, y),
-                0xE => self.shl_vx(x),
+                0xE => {
+                    self.ld_shift_vy(x, y);
+                    self.shl_vx(x);
+                }
                 _ => execute = false,
             },
             0x9000..=0x9FFF => self.sne_vx_vy(x, y),
@@ -166,6 +419,7 @@ impl Chip {
                 0x18 => self.ld_st_vx(x),
                 0x1E => self.add_i_vx(x),
                 0x29 => self.ld_f_vx(x),
+                0x30 if self.quirks.font.big.is_some() => self.ld_hf_vx(x),
                 0x33 => self.ld_b_vx(x),
                 0x55 => self.ld_vi_vx(x),
                 0x65 => self.ld_vx_vi(x),
@@ -174,7 +428,61 @@ impl Chip {
             _ => execute = false,
         }
 
-        execute
+        if !execute {
+            self.fault = Some(Fault::Instruction(opcode));
+        }
+        self.fault.is_none()
+    }
+
+    /// Returns the size of the memory reachable through the 16 bit addresses of the bus.
+    fn size(&self) -> usize {
+        self.bus.size().min(ADDRESS_SPACE)
+    }
+
+    /// Returns the bus address of a memory access, applying the memory policy of the quirks to
+    /// addresses beyond the end of the memory.
+    ///
+    /// # Parameters
+    /// * address - Address computed by an instruction.
+    ///
+    /// # Return
+    /// * None - If the access faults. The first fault is stored, so the tick stops.
+    fn address(&mut self, address: usize) -> Option<u16> {
+        let size = self.size();
+        if address < size {
+            return Some(address as u16);
+        }
+
+        match self.quirks.memory {
+            MemoryPolicy::Wrap => Some((address % size) as u16),
+            MemoryPolicy::Clamp => Some((size - 1) as u16),
+            MemoryPolicy::Fault => {
+                self.fault.get_or_insert(Fault::Address(address));
+                None
+            }
+        }
+    }
+
+    /// Reads a byte from memory. Faulting reads return 0.
+    ///
+    /// # Parameters
+    /// * address - Address computed by an instruction.
+    fn read(&mut self, address: usize) -> u8 {
+        match self.address(address) {
+            Some(address) => self.bus.read(address),
+            None => 0,
+        }
+    }
+
+    /// Writes a byte to memory. Faulting writes are ignored.
+    ///
+    /// # Parameters
+    /// * address - Address computed by an instruction.
+    /// * value - Byte to write.
+    fn write(&mut self, address: usize, value: u8) {
+        if let Some(address) = self.address(address) {
+            self.bus.write(address, value);
+        }
     }
 
     fn cls(&mut self, screen: &mut impl Screen) {
@@ -185,7 +493,13 @@ impl Chip {
     ///
     /// Decreases the stack pointer and retrieves the address stored at stack pointer. The
     /// instruction pointer is set to the stack pointer retrieved address.
+    ///
+    /// An empty stack stops the execution with a Fault::StackUnderflow fault.
     fn ret(&mut self) {
+        if self.sp == 0 {
+            self.fault = Some(Fault::StackUnderflow);
+            return;
+        }
         self.sp -= 1;
         self.ip = self.stack[self.sp] as usize;
     }
@@ -207,7 +521,13 @@ impl Chip {
     ///
     /// # Parameters
     /// * address - Memory address of the subroutine.
+    ///
+    /// A full stack stops the execution with a Fault::StackOverflow fault.
     fn call(&mut self, address: u16) {
+        if self.sp == STACK {
+            self.fault = Some(Fault::StackOverflow);
+            return;
+        }
         self.stack[self.sp] = self.ip as u16;
         self.sp += 1;
         self.ip = address as usize;
@@ -237,6 +557,18 @@ impl Chip {
         }
     }
 
+    /// Skips the next instruction if the value stored in register Vx is equal to the value
+    /// stored in register Vy.
+    ///
+    /// # Parameters
+    /// * x - Register number for Vx.
+    /// * y - Register number for Vy.
+    fn se_vx_vy(&mut self, x: u8, y: u8) {
+        if self.registers[x as usize] == self.registers[y as usize] {
+            self.ip += 2;
+        }
+    }
+
     /// Stores the opcode encoded value into register Vx.
     ///
     /// # Parameters
@@ -272,6 +604,7 @@ impl Chip {
     /// * y - Register number foy Vy.
     fn or_vx_vy(&mut self, x: u8, y: u8) {
         self.registers[x as usize] |= self.registers[y as usize];
+        self.reset_flag();
     }
 
     /// Performs *And* operation between Vx and Vy, storing the result in Vx.
@@ -281,6 +614,7 @@ impl Chip {
     /// * y - Register number foy Vy.
     fn and_vx_vy(&mut self, x: u8, y: u8) {
         self.registers[x as usize] &= self.registers[y as usize];
+        self.reset_flag();
     }
 
     /// Performs *Xor* operation between Vx and Vy, storing the result in Vx.
@@ -290,6 +624,25 @@ impl Chip {
     /// * y - Register number foy Vy.
     fn xor_vx_vy(&mut self, x: u8, y: u8) {
         self.registers[x as usize] ^= self.registers[y as usize];
+        self.reset_flag();
+    }
+
+    /// Resets VF after the logical operations with the VF reset quirk.
+    fn reset_flag(&mut self) {
+        if self.quirks.vf_reset {
+            self.registers[FLAG] = 0;
+        }
+    }
+
+    /// Loads Vy in Vx before 8XY6 and 8XYE with the shift quirk, so Vy is shifted.
+    ///
+    /// # Parameters
+    /// * x - Register number for Vx.
+    /// * y - Register number for Vy.
+    fn ld_shift_vy(&mut self, x: u8, y: u8) {
+        if self.quirks.shift_vy {
+            self.registers[x as usize] = self.registers[y as usize];
+        }
     }
 
     /// Performs *Add* operation between Vx and Vy, storing the result in Vx.
@@ -326,8 +679,9 @@ impl Chip {
     /// # Parameters
     /// * x - Register number for Vx.
     fn shr_vx(&mut self, x: u8) {
-        self.registers[FLAG] = self.registers[x as usize] & 0x01;
-        self.registers[x as usize] >>= 1;
+        let vx = self.registers[x as usize];
+        self.registers[x as usize] = vx >> 1;
+        self.registers[FLAG] = vx & 0x01;
     }
 
     /// Performs *Sub* operation to Vy, subtracting Vx and storing the result in Vx.
@@ -350,8 +704,9 @@ impl Chip {
     /// # Parameters
     /// * x - Register number for Vx.
     fn shl_vx(&mut self, x: u8) {
-        self.registers[FLAG] = self.registers[x as usize] >> 7;
-        self.registers[x as usize] <<= 1;
+        let vx = self.registers[x as usize];
+        self.registers[x as usize] = vx << 1;
+        self.registers[FLAG] = vx >> 7;
     }
 
     /// Skips the next instruction if Vx is different from Vy.
@@ -379,32 +734,74 @@ impl Chip {
     /// # Parameters
     /// * address - Address to add to register VO.
     fn jmp_v0_addr(&mut self, address: u16) {
-        self.ip = (address + self.registers[0 as usize] as u16) as usize;
+        self.ip = (address + self.registers[0] as u16) as usize;
     }
 
     fn rnd_vx_byte(&mut self, random: &mut impl Random, x: u8, mask: u8) {
         self.registers[x as usize] = random.range() & mask;
     }
 
+    /// Draws the sprite of the given number of lines stored at I, at the coordinates of Vx and
+    /// Vy. The sprite mode of the quirks defines how sprites are drawn at the screen edges and
+    /// the value of register VF.
+    ///
+    /// With the display wait quirk, the instruction is repeated until the next frame if a
+    /// sprite was already drawn in the current one.
+    ///
+    /// # Parameters
+    /// * screen - Screen to draw to.
+    /// * x - Register number for Vx.
+    /// * y - Register number for Vy.
+    /// * lines - Number of lines of the sprite.
     fn draw_vx_vy_nibble(&mut self, screen: &mut impl Screen, x: u8, y: u8, lines: u8) {
-        let vx = self.registers[x as usize];
-        let vy = self.registers[y as usize];
-        let mut collision = false;
+        if self.quirks.display_wait {
+            if self.drawn {
+                self.ip -= 2;
+                return;
+            }
+            self.drawn = true;
+        }
+
+        let mode = self.quirks.sprite;
+        let (vx, vy) = match mode {
+            SpriteMode::Wrap => (self.registers[x as usize], self.registers[y as usize]),
+            SpriteMode::Clip | SpriteMode::SchipRows => (
+                self.registers[x as usize] % SCREEN_WIDTH as u8,
+                self.registers[y as usize] % SCREEN_HEIGHT as u8,
+            ),
+        };
+        let mut collisions = 0;
 
         for line in 0..lines {
-            let data = self.memory[(self.i + line as u16) as usize];
+            // SCHIP counts the rows clipped at the bottom edge as collisions.
+            if mode == SpriteMode::SchipRows && vy as usize + line as usize >= SCREEN_HEIGHT {
+                collisions += 1;
+                continue;
+            }
+
+            let data = self.read(self.i as usize + line as usize);
+            let mut collision = false;
             for displace in 0..8 {
                 let bit = (data >> displace) & 0x1;
                 if bit == 1 {
-                    let new_x = vx.overflowing_add(7 - displace).0 % SCREEN_WIDTH as u8;
-                    let new_y = vy.overflowing_add(line).0 % SCREEN_HEIGHT as u8;
+                    let new_x = vx as usize + (7 - displace) as usize;
+                    let new_y = vy as usize + line as usize;
+                    if mode != SpriteMode::Wrap && (new_x >= SCREEN_WIDTH || new_y >= SCREEN_HEIGHT) {
+                        continue;
+                    }
 
-                    collision |= screen.draw(new_x, new_y);
+                    collision |= screen.draw((new_x % SCREEN_WIDTH) as u8, (new_y % SCREEN_HEIGHT) as u8);
                 }
             }
+            if collision {
+                collisions += 1;
+            }
         }
 
-        self.registers[FLAG] = if collision { 1 } else { 0 };
+        self.registers[FLAG] = match mode {
+            SpriteMode::SchipRows => collisions,
+            SpriteMode::Wrap | SpriteMode::Clip => if collisions > 0 { 1 } else { 0 },
+        };
     }
 
     fn skp_vx(&mut self, keypad: &impl Keypad, x: u8) {
@@ -455,13 +852,29 @@ impl Chip {
         self.timers[SOUND_TIMER] = self.registers[x as usize];
     }
 
+    /// Sets I to the value, truncated to the width of the I register.
+    ///
+    /// # Parameters
+    /// * value - New value of I.
+    fn set_i(&mut self, value: usize) {
+        self.i = match self.quirks.index {
+            IndexRegister::Wrap12 => (value & 0xFFF) as u16,
+            IndexRegister::Overflow | IndexRegister::Bits16 => value as u16,
+        };
+    }
+
     /// Add Vx register value to I. Store the result in I.
+    /// With the IndexRegister::Overflow quirk, register VF is set to 1 if the result exceeds
+    /// 0xFFF.
     ///
     /// # Parameters
     /// * x - Register number for Vx.
     fn add_i_vx(&mut self, x: u8) {
-        let vx = self.registers[x as usize] as u16;
-        self.i += vx;
+        let sum = self.i as usize + self.registers[x as usize] as usize;
+        self.set_i(sum);
+        if self.quirks.index == IndexRegister::Overflow {
+            self.registers[FLAG] = if sum > 0xFFF { 1 } else { 0 };
+        }
     }
 
     /// Set I to the location of the Vx font sprite.
@@ -470,7 +883,18 @@ impl Chip {
     /// # Parameters
     /// * x - Register number for Vx.
     fn ld_f_vx(&mut self, x: u8) {
-        self.i = (self.registers[x as usize] * CHARACTER_SIZE as u8) as u16;
+        let vx = self.registers[x as usize] as usize;
+        self.set_i(self.quirks.font_address as usize + vx * CHARACTER_SIZE);
+    }
+
+    /// Set I to the location of the Vx big font sprite, stored after the small font.
+    /// Each character has BIG_CHARACTER_SIZE length.
+    ///
+    /// # Parameters
+    /// * x - Register number for Vx.
+    fn ld_hf_vx(&mut self, x: u8) {
+        let vx = self.registers[x as usize] as usize;
+        self.set_i(self.quirks.font_address as usize + FONT_SIZE + vx * BIG_CHARACTER_SIZE);
     }
 
     /// Stores BCD representation of the value contained in register Vx.
@@ -480,43 +904,110 @@ impl Chip {
     /// * x - Register number for Vx.
     fn ld_b_vx(&mut self, x: u8) {
         let vx = self.registers[x as usize];
-        self.memory[self.i as usize] = vx / 100;
-        self.memory[(self.i + 1) as usize] = (vx % 100) / 10;
-        self.memory[(self.i + 2) as usize] = vx % 10;
+        let i = self.i as usize;
+        self.write(i, vx / 100);
+        self.write(i + 1, (vx % 100) / 10);
+        self.write(i + 2, vx % 10);
     }
 
     /// Stores V0 to Vx in memory, starting at address I.
-    /// I is set to I + x + 1
+    /// I is set to I + x + 1 with the load and store increment quirk.
     ///
     /// # Parameters
     /// * x - Register number for Vx.
     fn ld_vi_vx(&mut self, x: u8) {
         for register in 0..=x {
-            self.memory[(self.i + register as u16) as usize] = self.registers[register as usize];
+            self.write(self.i as usize + register as usize, self.registers[register as usize]);
+        }
+        if self.quirks.load_store_increment {
+            self.set_i(self.i as usize + x as usize + 1);
         }
-        self.i += (x + 1) as u16;
     }
 
     /// Fills V0 to Vx with values from memory starting at address I.
-    /// I is set to I + x + 1
+    /// I is set to I + x + 1 with the load and store increment quirk.
     ///
     /// # Parameters
     /// * x - Register number for Vx.
     fn ld_vx_vi(&mut self, x: u8) {
         for register in 0..=x {
-            self.registers[register as usize] = self.memory[(self.i + register as u16) as usize];
+            self.registers[register as usize] = self.read(self.i as usize + register as usize);
+        }
+        if self.quirks.load_store_increment {
+            self.set_i(self.i as usize + x as usize + 1);
         }
-        self.i += (x + 1) as u16;
     }
 }
 
 
+/// Functions available when the Chip uses a bus::Memory bus.
+impl<const SIZE: usize, const STACK: usize, const START: usize> Chip<Memory<SIZE>, STACK, START> {
+    /// Creates a Chip structure with default values and its memory initialized to 0.
+    ///
+    /// It is a const function, so chips can be placed in statics or initialized at compile
+    /// time, without being constructed on the stack.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use chip8vm::chip::{Chip, XoChip};
+    ///
+    /// static INITIAL: Chip = Chip::new();
+    ///
+    /// let mut chip = XoChip::new();
+    /// assert!(chip.load_rom(&[0; 0x8000]));
+    /// ```
+    pub const fn new() -> Self {
+        Chip::with_bus(Memory::new())
+    }
+
+    /// Initializes a Chip structure in place, with the same values as new, and returns it.
+    ///
+    /// The memory is zeroed directly in the provided location, so big chips can be created in
+    /// static or preallocated memory without using the stack.
+    ///
+    /// # Parameters
+    /// * chip - Location of the Chip structure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::mem::MaybeUninit;
+    /// use chip8vm::chip::Chip;
+    ///
+    /// let mut slots: [MaybeUninit<Chip>; 2] = [MaybeUninit::uninit(), MaybeUninit::uninit()];
+    /// let chip = Chip::init(&mut slots[0]);
+    /// chip.load_rom(&[0x12, 0x00]);
+    /// ```
+    pub fn init(chip: &mut MaybeUninit<Self>) -> &mut Self {
+        let chip_ptr = chip.as_mut_ptr();
+        // SAFETY: All the fields of a Chip with a Memory bus but the quirks and the fault are
+        // integers and arrays of integers, so zero is a valid value for them. The instruction
+        // pointer, the quirks and the fault are then set like in new.
+        unsafe {
+            ptr::write_bytes(chip_ptr, 0, 1);
+            ptr::addr_of_mut!((*chip_ptr).ip).write(START);
+            ptr::addr_of_mut!((*chip_ptr).quirks).write(Quirks::CHIP8);
+            ptr::addr_of_mut!((*chip_ptr).fault).write(None);
+            chip.assume_init_mut()
+        }
+    }
+
+    /// Returns the Chip8 VM memory, so frontends can inspect the program state, like scores
+    /// stored in memory.
+    pub fn memory(&self) -> &[u8] {
+        self.bus.data()
+    }
+}
+
 //
 // Tests
 //
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::needless_range_loop)]
 mod tests {
     use super::*;
+    use crate::framebuffer::FrameBuffer;
     use crate::PROGRAM_SIZE;
 
     struct TestRandom {}
@@ -541,6 +1032,28 @@ mod tests {
         fn pressed_key(&self) -> Option<u8> { None }
     }
 
+    /// Bus counting the accesses, with a read only device mapped at 0xF00.
+    #[derive(Default)]
+    struct TestBus {
+        memory: Memory,
+        reads: usize,
+        writes: usize,
+    }
+
+    impl Bus for TestBus {
+        fn read(&mut self, address: u16) -> u8 {
+            self.reads += 1;
+            if address == 0xF00 { 0x42 } else { self.memory.read(address) }
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            if address != 0xF00 {
+                self.writes += 1;
+                self.memory.write(address, value);
+            }
+        }
+    }
+
     fn prepare_vm(test_program: [u8; 10])-> (Chip, TestRandom, TestScreen, TestKeypad){
         let mut chip = Chip::default();
         let random = TestRandom{};
@@ -562,7 +1075,7 @@ mod tests {
         let mut loaded = true;
         let font = Font::default();
         for i in 0..font.set.len() {
-            if chip.memory[i] != font.set[i] {
+            if chip.memory()[i] != font.set[i] {
                 loaded = false;
                 break;
             }
@@ -578,7 +1091,7 @@ mod tests {
 
         let mut loaded = true;
         for i in 0..program_code.len() {
-            if chip.memory[PROG_START + i] != program_code[i] {
+            if chip.memory()[PROG_START + i] != program_code[i] {
                 loaded = false;
                 break;
             }
@@ -587,6 +1100,15 @@ mod tests {
         assert!(loaded);
     }
 
+    #[test]
+    fn memory() {
+        let program_code: [u8; 10] = [0x0, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        let (chip, _random, _screen, _keypad) = prepare_vm(program_code);
+
+        assert_eq!(chip.memory().len(), MEM_SIZE);
+        assert_eq!(chip.memory()[PROG_START + 9], 0x09);
+    }
+
     #[test]
     fn tick_timers_delay_not_zero() {
         let mut chip = Chip::default();
@@ -635,6 +1157,7 @@ mod tests {
         let result = chip.tick(&mut random, &mut screen, &keypad);
 
         assert_eq!(result, false);
+        assert_eq!(chip.fault(), Some(Fault::Instruction(0x0000)));
         assert_eq!(chip.ip, PROG_START + 2);
     }
 
@@ -651,6 +1174,17 @@ mod tests {
         assert_eq!(chip.sp, 0);
     }
 
+    #[test]
+    fn opcode_ret_empty_stack() {
+        let mut chip = Chip::default();
+
+        chip.ret();
+
+        assert_eq!(chip.fault, Some(Fault::StackUnderflow));
+        assert_eq!(chip.ip, PROG_START);
+        assert_eq!(chip.sp, 0);
+    }
+
     #[test]
     fn opcode_jmp() {
         let mut chip = Chip::default();
@@ -671,6 +1205,271 @@ mod tests {
         assert_eq!(chip.stack[0], PROG_START as u16);
     }
 
+    #[test]
+    fn opcode_call_full_stack() {
+        let mut chip = Chip::default();
+
+        for _level in 0..STACK_SIZE {
+            chip.call(0x205);
+        }
+        assert_eq!(chip.fault, None);
+        chip.call(0x300);
+
+        assert_eq!(chip.fault, Some(Fault::StackOverflow));
+        assert_eq!(chip.ip, 0x205);
+        assert_eq!(chip.sp, STACK_SIZE);
+    }
+
+    #[test]
+    fn memory_accesses_wrap() {
+        let mut chip = Chip::default();
+
+        chip.registers[0] = 1;
+        chip.registers[1] = 2;
+        chip.registers[2] = 3;
+        chip.i = 0xFFE;
+        chip.ld_vi_vx(2);
+
+        assert_eq!(chip.memory()[0xFFE..], [1, 2]);
+        assert_eq!(chip.memory()[0], 3);
+        assert_eq!(chip.i, 0x1001);
+
+        chip.i = 0xFFFF;
+        chip.registers[0] = 0xFF;
+        chip.add_i_vx(0);
+
+        assert_eq!(chip.i, 0xFE);
+    }
+
+    fn chip_with_index(index: IndexRegister) -> Chip {
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks {
+            index,
+            ..Quirks::CHIP8
+        });
+        chip
+    }
+
+    #[test]
+    fn index_register_wrap12() {
+        let mut chip = chip_with_index(IndexRegister::Wrap12);
+
+        chip.registers[1] = 0x20;
+        chip.registers[FLAG] = 0x05;
+        chip.i = 0xFF0;
+        chip.add_i_vx(1);
+
+        assert_eq!(chip.i, 0x010);
+        assert_eq!(chip.registers[FLAG], 0x05);
+
+        chip.i = 0xFFE;
+        chip.ld_vx_vi(2);
+
+        assert_eq!(chip.i, 0x001);
+    }
+
+    #[test]
+    fn index_register_overflow() {
+        let mut chip = chip_with_index(IndexRegister::Overflow);
+
+        chip.registers[1] = 0x20;
+        chip.i = 0xFD0;
+        chip.add_i_vx(1);
+
+        assert_eq!(chip.i, 0xFF0);
+        assert_eq!(chip.registers[FLAG], 0);
+
+        chip.add_i_vx(1);
+
+        assert_eq!(chip.i, 0x1010);
+        assert_eq!(chip.registers[FLAG], 1);
+
+        // VF as operand, the flag overwrites it.
+        chip.i = 0xFFF;
+        chip.registers[FLAG] = 0x01;
+        chip.add_i_vx(FLAG as u8);
+
+        assert_eq!(chip.i, 0x1000);
+        assert_eq!(chip.registers[FLAG], 1);
+    }
+
+    fn chip_with_sprite(sprite: SpriteMode, display_wait: bool) -> Chip {
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks {
+            sprite,
+            display_wait,
+            ..Quirks::CHIP8
+        });
+        chip.bus_mut().write(0x300, 0xFF);
+        chip.bus_mut().write(0x301, 0x81);
+        chip.i = 0x300;
+        chip
+    }
+
+    #[test]
+    fn sprite_wrap() {
+        let mut chip = chip_with_sprite(SpriteMode::Wrap, false);
+        let mut screen = FrameBuffer::default();
+
+        chip.registers[0] = 60 + SCREEN_WIDTH as u8;
+        chip.registers[1] = 31;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+
+        assert!(screen.is_on(60, 31) && screen.is_on(63, 31) && screen.is_on(0, 31) && screen.is_on(3, 31));
+        assert!(screen.is_on(60, 0) && screen.is_on(3, 0) && !screen.is_on(0, 0));
+        assert_eq!(chip.registers[FLAG], 0);
+
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+
+        assert_eq!(screen, FrameBuffer::default());
+        assert_eq!(chip.registers[FLAG], 1);
+    }
+
+    #[test]
+    fn sprite_clip() {
+        let mut chip = chip_with_sprite(SpriteMode::Clip, false);
+        let mut screen = FrameBuffer::default();
+
+        chip.registers[0] = 60 + SCREEN_WIDTH as u8;
+        chip.registers[1] = 31;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+
+        assert!(screen.is_on(60, 31) && screen.is_on(63, 31));
+        assert!(!screen.is_on(0, 31) && !screen.is_on(60, 0));
+        assert_eq!(chip.registers[FLAG], 0);
+
+        chip.registers[1] = 30;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+
+        assert_eq!(chip.registers[FLAG], 1);
+    }
+
+    #[test]
+    fn sprite_schip_rows() {
+        let mut chip = chip_with_sprite(SpriteMode::SchipRows, false);
+        let mut screen = FrameBuffer::default();
+
+        chip.registers[0] = 10;
+        chip.registers[1] = 10;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+        assert_eq!(chip.registers[FLAG], 0);
+
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+        assert_eq!(chip.registers[FLAG], 2);
+
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 1);
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 2);
+        assert_eq!(chip.registers[FLAG], 1);
+    }
+
+    #[test]
+    fn sprite_schip_rows_bottom_edge() {
+        let mut chip = chip_with_sprite(SpriteMode::SchipRows, false);
+        let mut screen = FrameBuffer::default();
+
+        // Sprite of 5 rows starting at the last 2 rows of the screen.
+        chip.registers[0] = 10;
+        chip.registers[1] = 30;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 5);
+        assert_eq!(chip.registers[FLAG], 3);
+        assert!(screen.is_on(10, 31));
+
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 5);
+        assert_eq!(chip.registers[FLAG], 5);
+        assert!(!screen.is_on(10, 31));
+    }
+
+    #[test]
+    fn sprite_display_wait() {
+        let mut chip = chip_with_sprite(SpriteMode::Wrap, true);
+        let mut screen = FrameBuffer::default();
+
+        chip.ip = 0x202;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 1);
+        assert_eq!(chip.ip, 0x202);
+        assert!(screen.is_on(0, 0));
+
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 1);
+        assert_eq!(chip.ip, 0x200);
+        assert!(screen.is_on(0, 0));
+
+        chip.tick_timers();
+        chip.ip = 0x202;
+        chip.draw_vx_vy_nibble(&mut screen, 0, 1, 1);
+        assert_eq!(chip.ip, 0x202);
+        assert!(!screen.is_on(0, 0));
+    }
+
+    #[test]
+    fn index_register_bits16() {
+        let mut chip = chip_with_index(IndexRegister::Bits16);
+
+        chip.registers[1] = 0x20;
+        chip.registers[FLAG] = 0x05;
+        chip.i = 0xFFF0;
+        chip.add_i_vx(1);
+
+        assert_eq!(chip.i, 0x0010);
+        assert_eq!(chip.registers[FLAG], 0x05);
+
+        chip.i = 0x1FF0;
+        chip.add_i_vx(1);
+
+        assert_eq!(chip.i, 0x2010);
+    }
+
+    #[test]
+    fn memory_policy_clamp() {
+        let program_code: [u8; 10] = [0xFF, 0x65, 0x1F, 0xFF, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0];
+        let (mut chip, mut random, mut screen, keypad) = prepare_vm(program_code);
+        chip.set_quirks(Quirks {
+            memory: MemoryPolicy::Clamp,
+            ..Quirks::CHIP8
+        });
+        chip.bus_mut().write(0xFFE, 0x12);
+        chip.bus_mut().write(0xFFF, 0x24);
+
+        chip.i = 0xFFE;
+        assert!(chip.tick(&mut random, &mut screen, &keypad));
+        assert_eq!(chip.registers[0..3], [0x12, 0x24, 0x24]);
+        assert_eq!(chip.registers[FLAG], 0x24);
+
+        // Fetch at 0xFFF reads 0x2424, a call to 0x424.
+        assert!(chip.tick(&mut random, &mut screen, &keypad));
+        assert!(chip.tick(&mut random, &mut screen, &keypad));
+        assert_eq!(chip.ip, 0x424);
+        assert_eq!(chip.stack(), [0x1001]);
+    }
+
+    #[test]
+    fn memory_policy_fault() {
+        let program_code: [u8; 10] = [0xF2, 0x55, 0x1F, 0xFE, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0];
+        let (mut chip, mut random, mut screen, keypad) = prepare_vm(program_code);
+        chip.set_quirks(Quirks {
+            memory: MemoryPolicy::Fault,
+            ..Quirks::CHIP8
+        });
+
+        chip.registers[0] = 1;
+        chip.registers[1] = 2;
+        chip.registers[2] = 3;
+        chip.i = 0xFFF;
+        assert!(!chip.tick(&mut random, &mut screen, &keypad));
+        assert_eq!(chip.fault(), Some(Fault::Address(0x1000)));
+        assert_eq!(chip.memory()[0xFFF], 1);
+        assert_eq!(chip.memory()[0], 0xF0);
+
+        // Fetch of the second byte of the instruction at 0xFFE.
+        chip.bus_mut().write(0xFFE, 0x1F);
+        chip.bus_mut().write(0xFFF, 0xFF);
+        assert!(chip.tick(&mut random, &mut screen, &keypad));
+        assert_eq!(chip.fault(), None);
+        assert!(chip.tick(&mut random, &mut screen, &keypad));
+        assert!(!chip.tick(&mut random, &mut screen, &keypad));
+        assert_eq!(chip.fault(), Some(Fault::Address(0x1000)));
+        assert_eq!(chip.ip, 0xFFF);
+    }
+
     #[test]
     fn opcode_se_vx_byte_without_skip() {
         let mut chip = Chip::default();
@@ -900,6 +1699,41 @@ mod tests {
         assert_eq!(chip.ip, PROG_START + 2);
     }
 
+    #[test]
+    fn opcode_se_vx_vy_without_skip() {
+        let mut chip = Chip::default();
+
+        chip.registers[1] = 2;
+        chip.registers[2] = 4;
+        chip.se_vx_vy(1, 2);
+
+        assert_eq!(chip.ip, PROG_START);
+    }
+
+    #[test]
+    fn opcode_se_vx_vy_with_skip() {
+        let mut chip = Chip::default();
+
+        chip.registers[1] = 4;
+        chip.registers[2] = 4;
+        chip.se_vx_vy(1, 2);
+
+        assert_eq!(chip.ip, PROG_START + 2);
+    }
+
+    #[test]
+    fn opcode_shifts_flag_register() {
+        let mut chip = Chip::default();
+
+        chip.registers[FLAG] = 0x81;
+        chip.shr_vx(FLAG as u8);
+        assert_eq!(chip.registers[FLAG], 1);
+
+        chip.registers[FLAG] = 0x81;
+        chip.shl_vx(FLAG as u8);
+        assert_eq!(chip.registers[FLAG], 1);
+    }
+
     #[test]
     fn opcode_ld_i_addr() {
         let mut chip = Chip::default();
@@ -966,7 +1800,7 @@ mod tests {
         chip.registers[2] = 6;
         chip.ld_f_vx(2);
 
-        assert_eq!(chip.i, (chip.registers[2] * CHARACTER_SIZE as u8) as u16);
+        assert_eq!(chip.i, chip.registers[2] as u16 * CHARACTER_SIZE as u16);
     }
 
     #[test]
@@ -976,9 +1810,9 @@ mod tests {
          chip.registers[2] = 128;
          chip.ld_b_vx(2);
 
-         assert_eq!(chip.memory[chip.i as usize], 1);
-         assert_eq!(chip.memory[(chip.i + 1) as usize], 2);
-         assert_eq!(chip.memory[(chip.i + 2) as usize], 8);
+         assert_eq!(chip.memory()[chip.i as usize], 1);
+         assert_eq!(chip.memory()[(chip.i + 1) as usize], 2);
+         assert_eq!(chip.memory()[(chip.i + 2) as usize], 8);
     }
 
     #[test]
@@ -991,31 +1825,31 @@ mod tests {
         chip.i = 0x200;
         chip.ld_vi_vx(FLAG as u8);
 
-        assert_eq!(chip.memory[0x200], 32);
-        assert_eq!(chip.memory[0x201], 0);
-        assert_eq!(chip.memory[0x202], 0);
-        assert_eq!(chip.memory[0x203], 0);
-        assert_eq!(chip.memory[0x204], 0);
-        assert_eq!(chip.memory[0x205], 0);
-        assert_eq!(chip.memory[0x206], 64);
-        assert_eq!(chip.memory[0x207], 0);
-        assert_eq!(chip.memory[0x208], 0);
-        assert_eq!(chip.memory[0x209], 0);
-        assert_eq!(chip.memory[0x20A], 0);
-        assert_eq!(chip.memory[0x20B], 0);
-        assert_eq!(chip.memory[0x20C], 0);
-        assert_eq!(chip.memory[0x20D], 0);
-        assert_eq!(chip.memory[0x20E], 0);
-        assert_eq!(chip.memory[0x20F], 128);
+        assert_eq!(chip.memory()[0x200], 32);
+        assert_eq!(chip.memory()[0x201], 0);
+        assert_eq!(chip.memory()[0x202], 0);
+        assert_eq!(chip.memory()[0x203], 0);
+        assert_eq!(chip.memory()[0x204], 0);
+        assert_eq!(chip.memory()[0x205], 0);
+        assert_eq!(chip.memory()[0x206], 64);
+        assert_eq!(chip.memory()[0x207], 0);
+        assert_eq!(chip.memory()[0x208], 0);
+        assert_eq!(chip.memory()[0x209], 0);
+        assert_eq!(chip.memory()[0x20A], 0);
+        assert_eq!(chip.memory()[0x20B], 0);
+        assert_eq!(chip.memory()[0x20C], 0);
+        assert_eq!(chip.memory()[0x20D], 0);
+        assert_eq!(chip.memory()[0x20E], 0);
+        assert_eq!(chip.memory()[0x20F], 128);
         assert_eq!(chip.i, 0x210);
     }
 
     #[test]
     fn opcode_ld_vx_vi() {
         let mut chip = Chip::default();
-        chip.memory[0x200] = 32;
-        chip.memory[0x206] = 64;
-        chip.memory[0x20F] = 128;
+        chip.bus.write(0x200, 32);
+        chip.bus.write(0x206, 64);
+        chip.bus.write(0x20F, 128);
         chip.i = 0x200;
         chip.ld_vx_vi(FLAG as u8);
 
@@ -1038,4 +1872,198 @@ mod tests {
         assert_eq!(chip.registers[0xF], 128);
         assert_eq!(chip.i, 0x210);
     }
+
+    #[test]
+    fn custom_bus() {
+        let mut chip: Chip<_> = Chip::with_bus(TestBus::default());
+        let mut program = [0; PROGRAM_SIZE];
+        program[..8].copy_from_slice(&[0xAF, 0x00, 0xF0, 0x65, 0xA3, 0x00, 0xF0, 0x33]);
+        chip.load_program(program);
+        let writes = chip.bus().writes;
+
+        for _tick in 0..4 {
+            assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        }
+
+        assert_eq!(chip.registers[0x0], 0x42);
+        assert_eq!(chip.bus().reads, 4 * 2 + 1);
+        assert_eq!(chip.bus().writes - writes, 3);
+        assert_eq!(chip.bus_mut().read(0x301), 6);
+    }
+
+    #[test]
+    fn platform_sizes() {
+        let mut eti = Eti660Chip::new();
+        let mut program = [0; PROGRAM_SIZE];
+        program[0] = 0x12;
+        program[PROGRAM_SIZE - 1] = 0x34;
+        eti.load_program(program);
+
+        assert_eq!(eti.ip, 0x600);
+        assert_eq!(eti.memory()[0x600], 0x12);
+        assert_eq!(eti.memory().len(), 0x1000);
+        assert!(!eti.load_rom(&[0; 0xA01]));
+        assert!(eti.load_rom(&[0; 0xA00]));
+
+        let mut xo = XoChip::new();
+        assert!(xo.load_rom(&[0x56; 0xFE00]));
+        assert_eq!(xo.memory()[0xFFFF], 0x56);
+        assert_eq!(xo.memory()[0x0], Font::default().set[0]);
+
+        let small: Chip<Memory<0x800>, 4> = Chip::new();
+        assert_eq!(small.stack.len(), 4);
+        assert_eq!(small.memory().len(), 0x800);
+    }
+
+    #[test]
+    fn const_and_in_place_initialization() {
+        static INITIAL: Eti660Chip = Eti660Chip::new();
+        let mut slot = MaybeUninit::<Eti660Chip>::uninit();
+        let chip = Chip::init(&mut slot);
+
+        assert_eq!(chip.ip, INITIAL.ip);
+        assert_eq!(chip.ip, 0x600);
+        assert_eq!(chip.i, INITIAL.i);
+        assert_eq!(chip.sp, INITIAL.sp);
+        assert_eq!(chip.registers, INITIAL.registers);
+        assert_eq!(chip.stack, INITIAL.stack);
+        assert_eq!(chip.timers, INITIAL.timers);
+        assert_eq!(chip.memory(), INITIAL.memory());
+    }
+
+    #[test]
+    fn font_quirks() {
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks {
+            font_address: 0x050,
+            ..Quirks::VIP
+        });
+        chip.load_rom(&[0xF1, 0x29, 0xF1, 0x30]);
+        chip.registers[1] = 0x4;
+
+        assert_eq!(chip.memory()[0x050..(0x050 + FONT_SIZE)], Font::VIP.set);
+        assert!(chip.memory()[..0x050].iter().all(|byte| *byte == 0));
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.i, 0x050 + 0x4 * CHARACTER_SIZE as u16);
+        assert!(!chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+    }
+
+    #[test]
+    fn arithmetic_quirks() {
+        // LD VF, 5; LD V0, 1; LD V1, 5; OR V0, V1; SHR V0, V2; LD I, 0x300; LD [I], V1
+        let rom = [0x6F, 0x05, 0x60, 0x01, 0x61, 0x05, 0x80, 0x11, 0x80, 0x26, 0xA3, 0x00, 0xF1, 0x55];
+
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks::VIP);
+        chip.load_rom(&rom);
+        for _tick in 0..4 {
+            assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        }
+        assert_eq!(chip.registers[FLAG], 0);
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.registers[0], 0x00);
+        assert_eq!(chip.registers[FLAG], 0);
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.i, 0x302);
+
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks::SCHIP);
+        chip.load_rom(&rom);
+        for _tick in 0..4 {
+            assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        }
+        assert_eq!(chip.registers[FLAG], 5);
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.registers[0], 0x02);
+        assert_eq!(chip.registers[FLAG], 1);
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.i, 0x300);
+    }
+
+    #[test]
+    fn font_address_overlapping_program() {
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks {
+            font_address: 0xFFFF,
+            ..Quirks::VIP
+        });
+        assert!(chip.load_rom(&[0xF1, 0x29]));
+        chip.registers[1] = 0x1;
+
+        assert_eq!(chip.quirks().font_address as usize, PROG_START - FONT_SIZE);
+        assert_eq!(chip.memory()[(PROG_START - FONT_SIZE)..PROG_START], Font::VIP.set);
+        assert_eq!(chip.memory()[PROG_START..(PROG_START + 2)], [0xF1, 0x29]);
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.i as usize, PROG_START - FONT_SIZE + CHARACTER_SIZE);
+
+        let mut eti = Eti660Chip::new();
+        eti.set_quirks(Quirks {
+            font_address: 0x5F0,
+            ..Quirks::ETI660
+        });
+        assert_eq!(eti.quirks().font_address, 0x600 - FONT_SIZE as u16);
+    }
+
+    #[test]
+    fn opcode_ld_hf_vx() {
+        let mut chip = Chip::default();
+        chip.set_quirks(Quirks::SCHIP);
+        chip.load_rom(&[0xF1, 0x30]);
+        chip.registers[1] = 0x9;
+
+        assert!(chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{}));
+        assert_eq!(chip.i, (FONT_SIZE + 0x9 * BIG_CHARACTER_SIZE) as u16);
+        assert_eq!(chip.memory()[chip.i as usize], Font::SCHIP.big.unwrap()[0x9 * BIG_CHARACTER_SIZE]);
+    }
+
+    #[test]
+    fn reset() {
+        let program_code: [u8; 10] = [0x22, 0x04, 0x00, 0x00, 0x63, 0x05, 0xF3, 0x15, 0xA3, 0x00];
+        let (mut chip, mut random, mut screen, keypad) = prepare_vm(program_code);
+        for _tick in 0..4 {
+            chip.tick(&mut random, &mut screen, &keypad);
+        }
+        chip.bus.write(0x300, 0x42);
+
+        chip.reset();
+
+        assert_eq!(chip.ip, PROG_START);
+        assert_eq!(chip.sp, 0);
+        assert_eq!(chip.i, 0);
+        assert_eq!(chip.registers, [0; NUM_RESGISTERS]);
+        assert_eq!(chip.stack, [0; STACK_SIZE]);
+        assert_eq!(chip.timers, [0; NUM_TIMERS]);
+        assert_eq!(chip.memory()[0x300], 0x42);
+        assert_eq!(chip.memory()[PROG_START..(PROG_START + 10)], program_code);
+    }
+
+    #[test]
+    fn reload() {
+        let mut chip = Chip::default();
+        chip.load_rom(&[0x63, 0x05]);
+        chip.tick(&mut TestRandom{}, &mut TestScreen{}, &TestKeypad{});
+        chip.bus.write(0x300, 0x42);
+
+        assert!(chip.reload(&[0x64, 0x06], Scrub::None, &mut TestRandom{}));
+        assert_eq!(chip.ip, PROG_START);
+        assert_eq!(chip.registers[3], 0);
+        assert_eq!(chip.memory()[0x300], 0x42);
+        assert_eq!(chip.memory()[PROG_START..(PROG_START + 2)], [0x64, 0x06]);
+
+        assert!(chip.reload(&[0x64, 0x06], Scrub::Zeros, &mut TestRandom{}));
+        assert_eq!(chip.memory()[0x300], 0);
+        assert_eq!(chip.memory()[..FONT_SIZE], Font::CHIP8.set);
+
+        assert!(chip.reload(&[0x64, 0x06], Scrub::Random, &mut TestRandom{}));
+        assert_eq!(chip.memory()[0x300], 1);
+        assert_eq!(chip.memory()[PROG_START + 2], 1);
+        assert_eq!(chip.memory()[PROG_START..(PROG_START + 2)], [0x64, 0x06]);
+
+        chip.ip = PROG_START + 2;
+        assert!(!chip.reload(&[0; MEM_SIZE], Scrub::Zeros, &mut TestRandom{}));
+        assert_eq!(chip.ip, PROG_START + 2);
+        assert_eq!(chip.memory()[0x300], 1);
+    }
 }
@@ -0,0 +1,948 @@
+use core::convert::TryFrom;
+use core::fmt;
+use core::str;
+
+use crate::chip::Chip;
+use crate::database::{self, KeyMap, DEFAULT_IPS};
+use crate::palette::{Palette, Rgb};
+use crate::quirks::{Quirks, SpriteMode};
+use crate::{Bus, PROGRAM_SIZE};
+
+/// Errors of the container loaders.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Error {
+    /// The program does not fit in the program area.
+    TooLarge,
+
+    /// The buffer provided to decode a cartridge is too small for its data.
+    Buffer,
+
+    /// The container is not a valid GIF image or cartridge.
+    Format,
+
+    /// Options text with a syntax error at the provided byte offset.
+    Syntax(usize),
+
+    /// Option with an invalid value, like a colour that is not `#RRGGBB`.
+    Value,
+
+    /// The cartridge contains Octo source code that is not a list of byte values. Only Octo
+    /// can assemble it.
+    Source,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::TooLarge => write!(f, "the program does not fit in memory"),
+            Error::Buffer => write!(f, "the cartridge data does not fit in the buffer"),
+            Error::Format => write!(f, "invalid container format"),
+            Error::Syntax(offset) => write!(f, "syntax error in the options at offset {}", offset),
+            Error::Value => write!(f, "invalid option value"),
+            Error::Source => write!(f, "the cartridge contains Octo source code"),
+        }
+    }
+}
+
+/// Configuration a program needs to run as intended.
+#[derive(Copy, Clone)]
+pub struct Config {
+    /// Quirks of the Chip8 VM.
+    pub quirks: Quirks,
+
+    /// Instructions executed per second, at least 60 so every frame executes an instruction.
+    pub ips: u32,
+
+    /// Colours of the program, if any.
+    pub palette: Option<Palette>,
+
+    /// Chip8 keys of the program actions.
+    pub keys: KeyMap,
+}
+
+/// Default implementation for Config structure.
+///
+/// Returns the CHIP8 quirks at DEFAULT_IPS, without colours nor key map.
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            quirks: Quirks::CHIP8,
+            ips: DEFAULT_IPS,
+            palette: None,
+            keys: KeyMap::NONE,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the number of instructions to execute per 60Hz frame.
+    pub fn ticks_per_frame(&self) -> u32 {
+        database::ticks_per_frame(self.ips)
+    }
+
+    /// Applies the options of a JSON object, like a sidecar options file or the options of an
+    /// Octo cartridge. See apply_toml for the supported options.
+    ///
+    /// # Parameters
+    /// * text - JSON object with the options.
+    pub fn apply_json(&mut self, text: &str) -> Result<(), Error> {
+        parse_json(text, &mut |table, key, value| self.set_profile(table, key, value))?;
+        parse_json(text, &mut |table, key, value| self.set(table, key, value))
+    }
+
+    /// Applies the options of a TOML document, like a sidecar options file. Supported options:
+    /// * quirks - Quirks profile: "chip8", "vip", "dream6800", "eti660" or "schip". It is
+    ///   applied before any other option.
+    /// * ips - Instructions executed per second.
+    /// * tickrate - Instructions executed per frame, like Octo.
+    /// * clipQuirks, vBlankQuirks - Octo quirks selecting clipped sprites and display wait.
+    /// * backgroundColor, fillColor, fillColor2, blendColor - Octo colours, as `#RRGGBB`.
+    /// * keys - Table with the up, down, left, right, a and b Chip8 keys.
+    ///
+    /// Other options, like the Octo quirks the Chip8 VM does not support, are ignored.
+    ///
+    /// # Parameters
+    /// * text - TOML document with the options.
+    pub fn apply_toml(&mut self, text: &str) -> Result<(), Error> {
+        parse_toml(text, &mut |table, key, value| self.set_profile(table, key, value))?;
+        parse_toml(text, &mut |table, key, value| self.set(table, key, value))
+    }
+
+    fn set_profile(&mut self, table: &str, key: &str, value: Value) -> Result<(), Error> {
+        if table.is_empty() && key == "quirks" {
+            self.quirks = match value {
+                Value::Text("chip8") => Quirks::CHIP8,
+                Value::Text("vip") => Quirks::VIP,
+                Value::Text("dream6800") => Quirks::DREAM6800,
+                Value::Text("eti660") => Quirks::ETI660,
+                Value::Text("schip") => Quirks::SCHIP,
+                _ => return Err(Error::Value),
+            };
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, table: &str, key: &str, value: Value) -> Result<(), Error> {
+        match (table, key, value) {
+            ("", "ips", Value::Integer(ips)) => {
+                self.ips = u32::try_from(ips).ok().filter(|ips| *ips >= 60).ok_or(Error::Value)?;
+            }
+            ("", "tickrate", Value::Integer(ticks)) => {
+                let ips = u32::try_from(ticks).ok().and_then(|ticks| ticks.checked_mul(60));
+                self.ips = ips.filter(|ips| *ips >= 60).ok_or(Error::Value)?;
+            }
+            ("", "clipQuirks", Value::Boolean(clip)) => {
+                self.quirks.sprite = match (clip, self.quirks.sprite) {
+                    (false, _) => SpriteMode::Wrap,
+                    (true, SpriteMode::SchipRows) => SpriteMode::SchipRows,
+                    (true, _) => SpriteMode::Clip,
+                }
+            }
+            ("", "vBlankQuirks", Value::Boolean(wait)) => self.quirks.display_wait = wait,
+            ("", "backgroundColor", value) => self.set_color(0, value)?,
+            ("", "fillColor", value) => self.set_color(1, value)?,
+            ("", "fillColor2", value) => self.set_color(2, value)?,
+            ("", "blendColor", value) => self.set_color(3, value)?,
+            ("", "quirks", _) => {}
+            ("", "ips", _) | ("", "tickrate", _) | ("", "clipQuirks", _) | ("", "vBlankQuirks", _) => return Err(Error::Value),
+            ("keys", key, value) => {
+                let action = match key {
+                    "up" => &mut self.keys.up,
+                    "down" => &mut self.keys.down,
+                    "left" => &mut self.keys.left,
+                    "right" => &mut self.keys.right,
+                    "a" => &mut self.keys.a,
+                    "b" => &mut self.keys.b,
+                    _ => return Ok(()),
+                };
+                *action = match value {
+                    Value::Integer(key) if (0..16).contains(&key) => Some(key as u8),
+                    _ => return Err(Error::Value),
+                };
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn set_color(&mut self, index: usize, value: Value) -> Result<(), Error> {
+        let color = match value {
+            Value::Text(text) => parse_color(text).ok_or(Error::Value)?,
+            _ => return Err(Error::Value),
+        };
+        self.palette.get_or_insert(Palette::MONOCHROME).colors[index] = color;
+        Ok(())
+    }
+}
+
+/// Program with its configuration, loaded from a container.
+#[derive(Copy, Clone)]
+pub struct Cartridge {
+    /// Program data, filled with zeros after the program.
+    pub program: [u8; PROGRAM_SIZE],
+
+    /// Size in bytes of the program.
+    pub size: usize,
+
+    /// Configuration of the program.
+    pub config: Config,
+}
+
+impl Cartridge {
+    /// Returns the program data.
+    pub fn rom(&self) -> &[u8] {
+        &self.program[..self.size]
+    }
+
+    /// Sets the quirks of the configuration and loads the program in the Chip8 VM.
+    ///
+    /// # Parameters
+    /// * chip - Chip8 VM.
+    ///
+    /// # Return
+    /// * false - If the program does not fit in memory. Nothing is loaded.
+    pub fn load<B: Bus, const STACK: usize, const START: usize>(&self, chip: &mut Chip<B, STACK, START>) -> bool {
+        chip.set_quirks(self.config.quirks);
+        chip.load_rom(self.rom())
+    }
+}
+
+/// Loads a plain binary program, like the .ch8 and .c8 files. Programs found in the ROM
+/// database get its configuration, and the default one otherwise.
+///
+/// # Parameters
+/// * data - Program data.
+pub fn load_binary(data: &[u8]) -> Result<Cartridge, Error> {
+    if data.len() > PROGRAM_SIZE {
+        return Err(Error::TooLarge);
+    }
+
+    let mut cartridge = Cartridge {
+        program: [0; PROGRAM_SIZE],
+        size: data.len(),
+        config: Config::default(),
+    };
+    cartridge.program[..data.len()].copy_from_slice(data);
+    if let Some(info) = database::lookup(data) {
+        cartridge.config = Config {
+            quirks: info.quirks,
+            ips: info.ips,
+            palette: info.palette,
+            keys: info.keys,
+        };
+    }
+    Ok(cartridge)
+}
+
+/// Loads an Octo cartridge: a GIF image whose pixels carry a JSON object with the program and
+/// its options in the 2 low bits of their colour index, 4 pixels per byte and most significant
+/// bits first, after a 32 bits big endian length.
+///
+/// Octo stores the assembly source code of the program, not its bytes, and this crate has no
+/// Octo assembler. Only data-only cartridges, whose source is just a list of byte values like
+/// the programs disassembled or pasted as data, are loaded. Nearly every cartridge written in
+/// Octo uses labels, aliases or mnemonics and returns Error::Source; assemble those with Octo
+/// and load the binary instead.
+///
+/// # Parameters
+/// * gif - GIF image data.
+/// * buffer - Buffer receiving the cartridge data. Its size limits the size of the data.
+/// * tables - Tables of the GIF decoder. They take 16 KiB, so small stacks should keep them in
+///   a static.
+pub fn load_octo_cart(gif: &[u8], buffer: &mut [u8], tables: &mut LzwTables) -> Result<Cartridge, Error> {
+    let mut size = 0;
+    let mut bits = 0;
+    let mut count = 0;
+    decode_gif(gif, tables, &mut |pixel| {
+        bits = bits << 2 | (pixel & 0x3);
+        count += 1;
+        if count == 4 {
+            if size < buffer.len() {
+                buffer[size] = bits;
+            }
+            size += 1;
+            bits = 0;
+            count = 0;
+        }
+    })?;
+
+    if size < 4 {
+        return Err(Error::Format);
+    }
+    let length = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+    if length > size - 4 {
+        return Err(Error::Format);
+    }
+    if 4 + length > buffer.len() {
+        return Err(Error::Buffer);
+    }
+    let text = str::from_utf8(&buffer[4..4 + length]).map_err(|_| Error::Format)?;
+
+    let mut cartridge = Cartridge {
+        program: [0; PROGRAM_SIZE],
+        size: 0,
+        config: Config::default(),
+    };
+    parse_json(text, &mut |table, key, value| match (table, key, value) {
+        ("", "program", Value::Text(source)) => parse_source(source, &mut cartridge),
+        ("options", key, value) => cartridge.config.set("", key, value),
+        _ => Ok(()),
+    })?;
+    Ok(cartridge)
+}
+
+/// Parses a `#RRGGBB` colour.
+fn parse_color(text: &str) -> Option<Rgb> {
+    let digits = text.strip_prefix('#')?;
+    if digits.len() != 6 {
+        return None;
+    }
+    let color = u32::from_str_radix(digits, 16).ok()?;
+    Some([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+}
+
+/// Parses an Octo source made of byte values in decimal, 0x hexadecimal or 0b binary
+/// notation, and comments. The source is a JSON string, with escape sequences.
+fn parse_source(source: &str, cartridge: &mut Cartridge) -> Result<(), Error> {
+    let mut token = [0u8; 10];
+    let mut length = 0;
+    let mut comment = false;
+    let characters = Unescape { text: source.as_bytes() }.chain(core::iter::once(b'\n'));
+
+    for character in characters {
+        if comment {
+            comment = character != b'\n';
+        } else if character == b'#' && length == 0 {
+            comment = true;
+        } else if character.is_ascii_whitespace() {
+            if length > 0 {
+                let value = parse_integer(&token[..length]).filter(|value| (0..=0xFF).contains(value));
+                if cartridge.size == PROGRAM_SIZE {
+                    return Err(Error::TooLarge);
+                }
+                cartridge.program[cartridge.size] = value.ok_or(Error::Source)? as u8;
+                cartridge.size += 1;
+                length = 0;
+            }
+        } else if length < token.len() {
+            token[length] = character;
+            length += 1;
+        } else {
+            return Err(Error::Source);
+        }
+    }
+    Ok(())
+}
+
+/// Iterator over the bytes of a JSON string with its escape sequences replaced. Unicode
+/// escapes are replaced by `?`, as they are not used by byte values nor options.
+struct Unescape<'a> {
+    text: &'a [u8],
+}
+
+impl<'a> Iterator for Unescape<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let (&character, rest) = self.text.split_first()?;
+        self.text = rest;
+        if character != b'\\' {
+            return Some(character);
+        }
+
+        let (&escaped, rest) = self.text.split_first()?;
+        self.text = rest;
+        Some(match escaped {
+            b'n' => b'\n',
+            b't' => b'\t',
+            b'r' => b'\r',
+            b'b' | b'f' => b' ',
+            b'u' => {
+                self.text = self.text.get(4..).unwrap_or(&[]);
+                b'?'
+            }
+            other => other,
+        })
+    }
+}
+
+/// Parses an integer with an optional sign, 0x, 0o or 0b prefixes and `_` separators.
+fn parse_integer(text: &[u8]) -> Option<i64> {
+    let (negative, text) = match text.split_first()? {
+        (b'-', rest) => (true, rest),
+        (b'+', rest) => (false, rest),
+        _ => (false, text),
+    };
+    let (radix, digits) = match text {
+        [b'0', b'x', digits @ ..] => (16, digits),
+        [b'0', b'o', digits @ ..] => (8, digits),
+        [b'0', b'b', digits @ ..] => (2, digits),
+        _ => (10, text),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut value: i64 = 0;
+    for digit in digits.iter().filter(|digit| **digit != b'_') {
+        let digit = (*digit as char).to_digit(radix)?;
+        value = value.checked_mul(radix as i64)?.checked_add(digit as i64)?;
+    }
+    Some(if negative { -value } else { value })
+}
+
+/// Option value. Text values of JSON keep their escape sequences.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Value<'a> {
+    Text(&'a str),
+    Integer(i64),
+    Boolean(bool),
+
+    /// Values not used by any option, like arrays or decimal numbers.
+    Other,
+}
+
+/// Maximum nesting of the values of JSON documents.
+const JSON_MAX_DEPTH: usize = 16;
+
+/// Parses a JSON object, calling the provided function for every value of the object and of
+/// the objects it contains, with the key of the contained object as table. Deeper values are
+/// skipped, and values nested deeper than JSON_MAX_DEPTH are rejected to bound the recursion.
+fn parse_json<'a>(text: &'a str, set: &mut impl FnMut(&str, &str, Value<'a>) -> Result<(), Error>) -> Result<(), Error> {
+    let mut parser = Json { text, position: 0 };
+    parser.object("", 0, set)?;
+    parser.whitespace();
+    if parser.position != text.len() {
+        return Err(Error::Syntax(parser.position));
+    }
+    Ok(())
+}
+
+struct Json<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Json<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn whitespace(&mut self) {
+        while self.peek().is_some_and(|character| character.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), Error> {
+        self.whitespace();
+        if self.peek() != Some(expected) {
+            return Err(Error::Syntax(self.position));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Parses a string, returning its content with the escape sequences.
+    fn string(&mut self) -> Result<&'a str, Error> {
+        self.expect(b'"')?;
+        let start = self.position;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.position += 2,
+                Some(_) => self.position += 1,
+                None => return Err(Error::Syntax(self.position)),
+            }
+        }
+        let end = self.position.min(self.text.len());
+        self.position += 1;
+        self.text.get(start..end).ok_or(Error::Syntax(start))
+    }
+
+    fn object(&mut self, table: &str, depth: usize, set: &mut impl FnMut(&str, &str, Value<'a>) -> Result<(), Error>) -> Result<(), Error> {
+        if depth > JSON_MAX_DEPTH {
+            return Err(Error::Syntax(self.position));
+        }
+        self.expect(b'{')?;
+        self.whitespace();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(());
+        }
+
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            self.whitespace();
+            match self.peek() {
+                Some(b'{') if depth == 0 => self.object(key, depth + 1, set)?,
+                _ => {
+                    let value = self.value(depth)?;
+                    set(table, key, value)?;
+                }
+            }
+
+            self.whitespace();
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(Error::Syntax(self.position)),
+            }
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value<'a>, Error> {
+        let start = self.position;
+        if depth > JSON_MAX_DEPTH {
+            return Err(Error::Syntax(start));
+        }
+        match self.peek() {
+            Some(b'"') => self.string().map(Value::Text),
+            Some(b'{') => self.object("", depth + 1, &mut |_, _, _| Ok(())).map(|_| Value::Other),
+            Some(b'[') => {
+                self.position += 1;
+                self.whitespace();
+                if self.peek() == Some(b']') {
+                    self.position += 1;
+                    return Ok(Value::Other);
+                }
+                loop {
+                    self.whitespace();
+                    self.value(depth + 1)?;
+                    self.whitespace();
+                    match self.peek() {
+                        Some(b',') => self.position += 1,
+                        Some(b']') => {
+                            self.position += 1;
+                            return Ok(Value::Other);
+                        }
+                        _ => return Err(Error::Syntax(self.position)),
+                    }
+                }
+            }
+            _ => {
+                while self.peek().is_some_and(|character| character.is_ascii_alphanumeric() || b"+-.".contains(&character)) {
+                    self.position += 1;
+                }
+                let token = &self.text[start..self.position];
+                match token {
+                    "true" => Ok(Value::Boolean(true)),
+                    "false" => Ok(Value::Boolean(false)),
+                    "null" => Ok(Value::Other),
+                    _ if token.bytes().any(|character| b".eE".contains(&character)) && token.parse::<f64>().is_ok() => Ok(Value::Other),
+                    _ => token.parse().map(Value::Integer).map_err(|_| Error::Syntax(start)),
+                }
+            }
+        }
+    }
+}
+
+/// Parses a TOML document of key and value pairs, calling the provided function for every
+/// value with its table. Strings, integers and booleans are supported.
+fn parse_toml<'a>(text: &'a str, set: &mut impl FnMut(&str, &str, Value<'a>) -> Result<(), Error>) -> Result<(), Error> {
+    let mut table = "";
+    let mut offset = 0;
+    for line in text.split('\n') {
+        let start = offset;
+        offset += line.len() + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let end = header.find(']').ok_or(Error::Syntax(start))?;
+            if !is_comment(&header[end + 1..]) {
+                return Err(Error::Syntax(start));
+            }
+            table = header[..end].trim();
+            continue;
+        }
+
+        let equals = line.find('=').ok_or(Error::Syntax(start))?;
+        let key = line[..equals].trim();
+        let key = key.strip_prefix('"').and_then(|key| key.strip_suffix('"')).unwrap_or(key);
+        let value = line[equals + 1..].trim();
+        let (value, rest) = match value.as_bytes().first() {
+            Some(quote @ (b'"' | b'\'')) => {
+                let end = value[1..].find(*quote as char).ok_or(Error::Syntax(start))?;
+                (Value::Text(&value[1..end + 1]), &value[end + 2..])
+            }
+            _ => {
+                let end = value.find('#').unwrap_or(value.len());
+                let token = value[..end].trim();
+                let value = match token {
+                    "true" => Value::Boolean(true),
+                    "false" => Value::Boolean(false),
+                    _ => parse_integer(token.as_bytes()).map(Value::Integer).ok_or(Error::Syntax(start))?,
+                };
+                (value, "")
+            }
+        };
+        if !is_comment(rest) {
+            return Err(Error::Syntax(start));
+        }
+        set(table, key, value)?;
+    }
+    Ok(())
+}
+
+/// Returns true if the rest of a TOML line is empty or a comment.
+fn is_comment(rest: &str) -> bool {
+    let rest = rest.trim();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+/// Decodes the images of a GIF, calling the provided function with the colour index of every
+/// pixel, frame by frame and row by row.
+fn decode_gif(data: &[u8], tables: &mut LzwTables, pixel: &mut impl FnMut(u8)) -> Result<(), Error> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return Err(Error::Format);
+    }
+    let mut reader = Reader { data, position: 6 };
+
+    let flags = reader.bytes(7)?[4];
+    if flags & 0x80 != 0 {
+        reader.bytes(3 << ((flags & 0x7) + 1))?;
+    }
+
+    loop {
+        match reader.byte()? {
+            0x21 => {
+                reader.byte()?;
+                reader.skip_blocks()?;
+            }
+            0x2C => {
+                let descriptor = reader.bytes(9)?;
+                let width = u16::from_le_bytes([descriptor[4], descriptor[5]]) as usize;
+                let height = u16::from_le_bytes([descriptor[6], descriptor[7]]) as usize;
+                let flags = descriptor[8];
+                if flags & 0x40 != 0 {
+                    // Interlaced images store the rows out of order.
+                    return Err(Error::Format);
+                }
+                if flags & 0x80 != 0 {
+                    reader.bytes(3 << ((flags & 0x7) + 1))?;
+                }
+                let min_code_size = reader.byte()?;
+                let mut pixels = width * height;
+                lzw_decode(&mut reader, min_code_size, tables, &mut |index| {
+                    if pixels > 0 {
+                        pixels -= 1;
+                        pixel(index);
+                    }
+                })?;
+            }
+            0x3B => return Ok(()),
+            _ => return Err(Error::Format),
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        let bytes = self.data.get(self.position..self.position + count).ok_or(Error::Format)?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn skip_blocks(&mut self) -> Result<(), Error> {
+        loop {
+            match self.byte()? {
+                0 => return Ok(()),
+                size => self.bytes(size as usize)?,
+            };
+        }
+    }
+}
+
+const LZW_MAX_CODES: usize = 4096;
+
+/// Tables of the LZW decoder of GIF images, supplied by the caller of load_octo_cart so they
+/// can be placed in a static instead of the stack.
+pub struct LzwTables {
+    prefix: [u16; LZW_MAX_CODES],
+    suffix: [u8; LZW_MAX_CODES],
+    string: [u8; LZW_MAX_CODES],
+}
+
+impl LzwTables {
+    /// Creates the decoder tables.
+    pub const fn new() -> Self {
+        LzwTables {
+            prefix: [0; LZW_MAX_CODES],
+            suffix: [0; LZW_MAX_CODES],
+            string: [0; LZW_MAX_CODES],
+        }
+    }
+}
+
+/// Default implementation for LzwTables structure.
+impl Default for LzwTables {
+    fn default() -> Self {
+        LzwTables::new()
+    }
+}
+
+/// Decodes the LZW compressed data sub-blocks of an image.
+fn lzw_decode(
+    reader: &mut Reader,
+    min_code_size: u8,
+    tables: &mut LzwTables,
+    output: &mut impl FnMut(u8),
+) -> Result<(), Error> {
+    if !(2..=8).contains(&min_code_size) {
+        return Err(Error::Format);
+    }
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let LzwTables { prefix, suffix, string } = tables;
+    for code in 0..clear {
+        suffix[code as usize] = code as u8;
+    }
+
+    let mut block = 0;
+    let mut bits = 0u32;
+    let mut available = 0;
+    let mut code_size = min_code_size + 1;
+    let mut next = clear + 2;
+    let mut previous: Option<u16> = None;
+    let mut finished = false;
+
+    loop {
+        // Reads the next code from the sub-blocks.
+        while available < code_size && !finished {
+            if block == 0 {
+                block = reader.byte()?;
+                if block == 0 {
+                    finished = true;
+                    break;
+                }
+            }
+            bits |= (reader.byte()? as u32) << available;
+            available += 8;
+            block -= 1;
+        }
+        if available < code_size {
+            return Ok(());
+        }
+        let code = (bits & ((1 << code_size) - 1)) as u16;
+        bits >>= code_size;
+        available -= code_size;
+
+        if code == clear {
+            code_size = min_code_size + 1;
+            next = clear + 2;
+            previous = None;
+            continue;
+        }
+        if code == end {
+            if !finished {
+                reader.skip_blocks()?;
+            }
+            return Ok(());
+        }
+
+        // Writes the string of the code backwards, adding the first byte of the previous
+        // string for the code being defined.
+        let (mut current, mut length) = match previous {
+            _ if code < next => (code, 0),
+            Some(previous) if code == next => (previous, 1),
+            _ => return Err(Error::Format),
+        };
+        let extra = length;
+        while current >= clear {
+            if length == LZW_MAX_CODES {
+                return Err(Error::Format);
+            }
+            string[length] = suffix[current as usize];
+            length += 1;
+            current = prefix[current as usize];
+        }
+        if length == LZW_MAX_CODES {
+            return Err(Error::Format);
+        }
+        string[length] = current as u8;
+        length += 1;
+        let first = current as u8;
+        if extra == 1 {
+            string[0] = first;
+        }
+        for byte in string[..length].iter().rev() {
+            output(*byte);
+        }
+
+        if let Some(previous) = previous {
+            if (next as usize) < LZW_MAX_CODES {
+                prefix[next as usize] = previous;
+                suffix[next as usize] = first;
+                next += 1;
+                if next == 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            }
+        }
+        previous = Some(code);
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    /// Builds an Octo cartridge with the provided JSON payload, split in 2 frames of 64 pixels
+    /// wide images. Pixels use 16 colours, so the high bits of their indices are not data.
+    fn cartridge(json: &str) -> Vec<u8> {
+        let mut payload = (json.len() as u32).to_be_bytes().to_vec();
+        payload.extend_from_slice(json.as_bytes());
+        let mut pixels: Vec<u8> = payload
+            .iter()
+            .flat_map(|byte| (0..4).rev().map(move |pair| (byte >> (pair * 2) & 0x3) | 0x4))
+            .collect();
+        let height = pixels.len().div_ceil(128) as u16;
+        pixels.resize(height as usize * 128, 0xC);
+
+        let palette: Vec<u8> = (0..16u8).flat_map(|index| [index * 16; 3]).collect();
+        let mut gif = Vec::new();
+        {
+            let mut encoder = ::gif::Encoder::new(&mut gif, 64, height, &palette).unwrap();
+            for frame in pixels.chunks(pixels.len() / 2) {
+                encoder.write_frame(&::gif::Frame::from_indexed_pixels(64, height, frame, None)).unwrap();
+            }
+        }
+        gif
+    }
+
+    #[test]
+    fn binary() {
+        let brix = load_binary(include_bytes!("../games/BRIX")).unwrap();
+        assert_eq!(brix.rom(), &include_bytes!("../games/BRIX")[..]);
+        assert_eq!(brix.config.keys.left, Some(0x4));
+
+        let unknown = load_binary(&[0x12, 0x00]).unwrap();
+        assert_eq!(unknown.config.ips, DEFAULT_IPS);
+        assert!(unknown.config.palette.is_none());
+
+        assert_eq!(load_binary(&[0; PROGRAM_SIZE + 1]).err(), Some(Error::TooLarge));
+    }
+
+    #[test]
+    fn json_options() {
+        let mut config = Config::default();
+        config
+            .apply_json(
+                r##"{
+                    "tickrate": 15, "vBlankQuirks": false, "quirks": "vip", "shiftQuirks": true,
+                    "fillColor": "#FFCC00", "maxSize": [3215, 3583], "scale": 1.5,
+                    "keys": { "left": 4, "a": 10, "players": { "up": 1 } }
+                }"##,
+            )
+            .unwrap();
+
+        assert_eq!(config.ips, 900);
+        assert_eq!(config.quirks.font.set, Quirks::VIP.font.set);
+        assert_eq!(config.quirks.sprite, SpriteMode::Clip);
+        assert!(!config.quirks.display_wait);
+        assert_eq!(config.palette.unwrap().colors[0..2], [[0x00, 0x00, 0x00], [0xFF, 0xCC, 0x00]]);
+        assert_eq!(config.keys, KeyMap { left: Some(0x4), a: Some(0xA), ..KeyMap::NONE });
+
+        assert_eq!(config.apply_json(r#"{"fillColor": "yellow"}"#).err(), Some(Error::Value));
+        assert_eq!(config.apply_json(r#"{"tickrate" 15}"#).err(), Some(Error::Syntax(12)));
+        assert_eq!(config.apply_json(r#"{"keys": {"up": 16}}"#).err(), Some(Error::Value));
+        assert_eq!(config.apply_json(r#"{"tickrate": 100000000}"#).err(), Some(Error::Value));
+        assert_eq!(config.apply_json(r#"{"tickrate": 0}"#).err(), Some(Error::Value));
+        let nested = ["{\"a\": ", &"[".repeat(200_000), &"]".repeat(200_000), "}"].concat();
+        assert_eq!(config.apply_json(&nested).err(), Some(Error::Syntax(23)));
+        let nested = ["{\"a\": ", &"[".repeat(16), &"]".repeat(16), "}"].concat();
+        assert!(config.apply_json(&nested).is_ok());
+        assert_eq!(config.apply_json(r#"{"ips": 4294967296}"#).err(), Some(Error::Value));
+        assert_eq!(config.apply_json(r#"{"ips": 59}"#).err(), Some(Error::Value));
+        assert_eq!(config.ips, 900);
+    }
+
+    #[test]
+    fn toml_options() {
+        let mut config = Config::default();
+        config
+            .apply_toml(
+                "# Options\nips = 1_200\nclipQuirks = true # Clip\nquirks = 'schip'\n\
+                 backgroundColor = \"#102030\"\n\n[keys]\nup = 0x2\n\"down\" = 8\n",
+            )
+            .unwrap();
+
+        assert_eq!(config.ticks_per_frame(), 20);
+        assert_eq!(config.quirks.sprite, SpriteMode::SchipRows);
+        assert_eq!(config.palette.unwrap().colors[0], [0x10, 0x20, 0x30]);
+        assert_eq!(config.keys, KeyMap { up: Some(0x2), down: Some(0x8), ..KeyMap::NONE });
+
+        assert_eq!(config.apply_toml("ips = 60\nquirks = \"amiga\"\n").err(), Some(Error::Value));
+        assert_eq!(config.apply_toml("ips = 60\nips 60\n").err(), Some(Error::Syntax(9)));
+        assert_eq!(config.apply_toml("ips = 60 60\n").err(), Some(Error::Syntax(0)));
+    }
+
+    #[test]
+    fn octo_cartridge() {
+        let gif = cartridge(
+            r##"{"program":"# Loop\n0x12 0x00\n\t0b11100000 238","options":{"tickrate":20,"clipQuirks":true,"quietColor":"#000000"}}"##,
+        );
+        let mut buffer = [0; 256];
+
+        let mut tables = LzwTables::new();
+
+        let cartridge = load_octo_cart(&gif, &mut buffer, &mut tables).unwrap();
+        assert_eq!(cartridge.rom(), [0x12, 0x00, 0xE0, 0xEE]);
+        assert_eq!(cartridge.config.ips, 1200);
+        assert_eq!(cartridge.config.quirks.sprite, SpriteMode::Clip);
+
+        let mut chip = Chip::default();
+        assert!(cartridge.load(&mut chip));
+        assert_eq!(&chip.memory()[0x200..0x204], cartridge.rom());
+        assert_eq!(chip.quirks().sprite, SpriteMode::Clip);
+
+        assert_eq!(load_octo_cart(&gif, &mut [0; 16], &mut tables).err(), Some(Error::Buffer));
+        assert_eq!(load_octo_cart(&gif[..gif.len() - 8], &mut buffer, &mut tables).err(), Some(Error::Format));
+    }
+
+    #[test]
+    fn octo_source() {
+        let gif = cartridge(r#"{"program":": main\n  loop again"}"#);
+        let mut tables = LzwTables::new();
+        assert_eq!(load_octo_cart(&gif, &mut [0; 256], &mut tables).err(), Some(Error::Source));
+    }
+
+    #[test]
+    fn lzw_code_sizes() {
+        // Enough different pixels to grow the codes to 12 bits and clear the table.
+        let pixels: Vec<u8> = (0..64 * 256).map(|index: usize| ((index * 7 / 3) ^ (index >> 5)) as u8 & 0xF).collect();
+        let palette: Vec<u8> = (0..16u8).flat_map(|index| [index; 3]).collect();
+        let mut gif = Vec::new();
+        {
+            let mut encoder = ::gif::Encoder::new(&mut gif, 64, 256, &palette).unwrap();
+            encoder.write_frame(&::gif::Frame::from_indexed_pixels(64, 256, &pixels[..], None)).unwrap();
+        }
+
+        let mut decoded = Vec::new();
+        decode_gif(&gif, &mut LzwTables::new(), &mut |pixel| decoded.push(pixel)).unwrap();
+        assert!(decoded == pixels);
+    }
+}
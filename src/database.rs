@@ -0,0 +1,377 @@
+use crate::palette::Palette;
+use crate::quirks::{Quirks, SpriteMode};
+
+/// Constant defining the size in bytes of a SHA-1 hash.
+pub const HASH_SIZE: usize = 20;
+
+/// Constant defining the default number of instructions executed per second.
+pub const DEFAULT_IPS: u32 = 600;
+
+/// SHA-1 hash of a program.
+pub type Hash = [u8; HASH_SIZE];
+
+/// Platforms programs were written for.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Platform {
+    /// COSMAC VIP, running the original interpreter.
+    Vip,
+
+    /// HP48 calculators running CHIP-48, and the DOS interpreters derived from it.
+    Chip48,
+
+    /// HP48 calculators running SCHIP.
+    Schip,
+}
+
+/// Chip8 keys of the actions of a program. Frontends map their direction and action keys to
+/// them, in addition to the keypad layout.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct KeyMap {
+    pub up: Option<u8>,
+    pub down: Option<u8>,
+    pub left: Option<u8>,
+    pub right: Option<u8>,
+
+    /// Main action, like fire or drop.
+    pub a: Option<u8>,
+
+    /// Secondary action, like rotate or start.
+    pub b: Option<u8>,
+}
+
+impl KeyMap {
+    /// Key map without any action.
+    pub const NONE: KeyMap = KeyMap {
+        up: None,
+        down: None,
+        left: None,
+        right: None,
+        a: None,
+        b: None,
+    };
+}
+
+/// Metadata of a known program.
+#[derive(Copy, Clone)]
+pub struct RomInfo {
+    /// SHA-1 hash of the program.
+    pub hash: Hash,
+
+    pub title: &'static str,
+    pub author: &'static str,
+    pub platform: Platform,
+
+    /// Quirks the program needs to run as intended.
+    pub quirks: Quirks,
+
+    /// Recommended number of instructions executed per second.
+    pub ips: u32,
+
+    /// Chip8 keys of the program actions.
+    pub keys: KeyMap,
+
+    /// Recommended colours, if any.
+    pub palette: Option<Palette>,
+}
+
+impl RomInfo {
+    /// Returns the number of instructions to execute per 60Hz frame.
+    pub fn ticks_per_frame(&self) -> u32 {
+        ticks_per_frame(self.ips)
+    }
+}
+
+/// Returns the number of instructions to execute per 60Hz frame.
+///
+/// # Parameters
+/// * ips - Instructions executed per second.
+pub(crate) const fn ticks_per_frame(ips: u32) -> u32 {
+    ips / 60
+}
+
+/// Returns the metadata of a known program.
+///
+/// # Parameters
+/// * rom - Program to look up.
+///
+/// # Return
+/// * Option<&RomInfo> - Metadata of the program, or None if the program is unknown.
+pub fn lookup(rom: &[u8]) -> Option<&'static RomInfo> {
+    lookup_hash(&sha1(rom))
+}
+
+/// Returns the metadata of a known program by its SHA-1 hash.
+///
+/// # Parameters
+/// * hash - SHA-1 hash of the program.
+pub fn lookup_hash(hash: &Hash) -> Option<&'static RomInfo> {
+    DATABASE.iter().find(|info| info.hash == *hash)
+}
+
+/// Returns the metadata of all the known programs.
+pub fn entries() -> &'static [RomInfo] {
+    &DATABASE
+}
+
+/// Computes the SHA-1 hash of the provided data.
+///
+/// # Parameters
+/// * data - Data to hash.
+pub fn sha1(data: &[u8]) -> Hash {
+    let mut state: [u32; 5] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476, 0xC3D2_E1F0];
+
+    // The data is followed by 0x80, zeros up to 8 bytes before the end of a block and the
+    // length in bits.
+    let length = (data.len() as u64 * 8).to_be_bytes();
+    let blocks = (data.len() + 1 + length.len() + 63) / 64;
+    for block in 0..blocks {
+        let mut chunk = [0u8; 64];
+        for (offset, byte) in chunk.iter_mut().enumerate() {
+            let position = block * 64 + offset;
+            *byte = match position {
+                _ if position < data.len() => data[position],
+                _ if position == data.len() => 0x80,
+                _ if position >= blocks * 64 - length.len() => length[position + length.len() - blocks * 64],
+                _ => 0,
+            };
+        }
+        compress(&mut state, &chunk);
+    }
+
+    let mut hash = [0; HASH_SIZE];
+    for (bytes, word) in hash.chunks_mut(4).zip(state.iter()) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    hash
+}
+
+fn compress(state: &mut [u32; 5], chunk: &[u8; 64]) {
+    let mut words = [0u32; 80];
+    for (word, bytes) in words.iter_mut().zip(chunk.chunks(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    for i in 16..80 {
+        words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+    for (i, word) in words.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+            20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+            _ => (b ^ c ^ d, 0xCA62_C1D6),
+        };
+        let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (value, result) in state.iter_mut().zip([a, b, c, d, e].iter()) {
+        *value = value.wrapping_add(*result);
+    }
+}
+
+/// Returns the hash of a hexadecimal string at compile time.
+const fn hash(text: &str) -> Hash {
+    const fn digit(character: u8) -> u8 {
+        match character {
+            b'0'..=b'9' => character - b'0',
+            b'a'..=b'f' => character - b'a' + 10,
+            _ => panic!("invalid hash digit"),
+        }
+    }
+
+    let text = text.as_bytes();
+    let mut hash = [0; HASH_SIZE];
+    let mut i = 0;
+    while i < HASH_SIZE {
+        hash[i] = digit(text[i * 2]) << 4 | digit(text[i * 2 + 1]);
+        i += 1;
+    }
+    hash
+}
+
+/// Key map of the games moving with 4, 6 and acting with 5.
+const KEYS_456: KeyMap = KeyMap {
+    left: Some(0x4),
+    right: Some(0x6),
+    a: Some(0x5),
+    ..KeyMap::NONE
+};
+
+/// Key map of the games moving with 2, 8, 4, 6 and acting with 5.
+const KEYS_2468: KeyMap = KeyMap {
+    up: Some(0x2),
+    down: Some(0x8),
+    left: Some(0x4),
+    right: Some(0x6),
+    a: Some(0x5),
+    b: None,
+};
+
+/// Key map of the games moving with 3, 6, 7, 8.
+const KEYS_3678: KeyMap = KeyMap {
+    up: Some(0x3),
+    down: Some(0x6),
+    left: Some(0x7),
+    right: Some(0x8),
+    ..KeyMap::NONE
+};
+
+/// Key map of the first player of the pong games.
+const KEYS_PONG: KeyMap = KeyMap {
+    up: Some(0x1),
+    down: Some(0x4),
+    ..KeyMap::NONE
+};
+
+const fn entry(hash_text: &str, title: &'static str, author: &'static str, platform: Platform, keys: KeyMap) -> RomInfo {
+    RomInfo {
+        hash: hash(hash_text),
+        title,
+        author,
+        platform,
+        quirks: match platform {
+            Platform::Vip => Quirks::VIP,
+            Platform::Chip48 => Quirks::CHIP8,
+            Platform::Schip => Quirks::SCHIP,
+        },
+        ips: DEFAULT_IPS,
+        keys,
+        palette: None,
+    }
+}
+
+/// Known programs, including the programs of the games folder.
+static DATABASE: [RomInfo; 23] = [
+    entry("ea9af3c09b0d9e265fcd92bcc5d51a2939fdf27a", "15 Puzzle", "Roger Ivie", Platform::Chip48, KEYS_2468),
+    RomInfo {
+        ips: 1000,
+        ..entry("d40abc54374e4343639f993e897e00904ddf85d9", "Blinky", "Hans Christian Egeberg", Platform::Chip48, KEYS_3678)
+    },
+    RomInfo {
+        quirks: Quirks {
+            sprite: SpriteMode::Clip,
+            ..Quirks::CHIP8
+        },
+        ..entry("6f6509f38220e057a7e32ebb22dd353c1078e3e7", "Blitz", "David Winter", Platform::Chip48, KeyMap {
+            a: Some(0x5),
+            ..KeyMap::NONE
+        })
+    },
+    entry("f13766c14aeb02ad8d4d103cb5eadd282d20cddc", "Brix", "Andreas Gustafsson", Platform::Chip48, KEYS_456),
+    entry("2d10c07b532f4fa7c07a07324ba26ca39fe484fd", "Connect 4", "David Winter", Platform::Chip48, KEYS_456),
+    entry("5260f8931e0e9f41e555b382a14a88368e3ed886", "Guess", "David Winter", Platform::Chip48, KEYS_456),
+    entry("050f07a54371da79f924dd0227b89d07b4f2aed0", "Hidden", "David Winter", Platform::Chip48, KEYS_2468),
+    entry("f100197f0f2f05b4f3c8c31ab9c2c3930d3e9571", "Space Invaders", "David Winter", Platform::Chip48, KEYS_456),
+    entry("d6fa9dc9005dc0496f39ba52fef56f9fd0a5a158", "Kaleidoscope", "Joseph Weisbecker", Platform::Vip, KeyMap {
+        b: Some(0x0),
+        ..KEYS_2468
+    }),
+    entry("b9272ae1acdaaa79ab649f6b48b72088ca2b1d74", "Maze", "David Winter", Platform::Chip48, KeyMap::NONE),
+    entry("d979858bb9ffd07b48f52f92a8bcac0199f3623e", "Merlin", "David Winter", Platform::Chip48, KeyMap {
+        up: Some(0x4),
+        down: Some(0x7),
+        left: Some(0x5),
+        right: Some(0x8),
+        ..KeyMap::NONE
+    }),
+    entry("0d0cc129dad3c45ba672f85fec71a668232212cc", "Missile Command", "David Winter", Platform::Chip48, KeyMap {
+        a: Some(0x8),
+        ..KeyMap::NONE
+    }),
+    RomInfo {
+        palette: Some(Palette::CLASSIC_GREEN),
+        ..entry("b232ef880bd6060fb45fa6effed7edf0ae95670e", "Pong", "Paul Vervalin", Platform::Chip48, KEYS_PONG)
+    },
+    RomInfo {
+        palette: Some(Palette::CLASSIC_GREEN),
+        ..entry("a60611339661e3ab2d8af024ad1da5880a6f8665", "Pong 2", "David Winter", Platform::Chip48, KEYS_PONG)
+    },
+    entry("1293db0ccccbe7dd3fc5a09a2abc5d7b175e18e0", "Puzzle", "Unknown", Platform::Chip48, KEYS_2468),
+    RomInfo {
+        ips: 1000,
+        ..entry("1bdb4ddaa7049266fa3226851f28855a365cfd12", "Syzygy", "Roy Trevino", Platform::Chip48, KEYS_3678)
+    },
+    entry("18b9d15f4c159e1f0ed58c2d8ec1d89325d3a3b6", "Tank", "Unknown", Platform::Chip48, KEYS_2468),
+    entry("5f518084744bf3cb8733f6e5454dfd1634320563", "Tetris", "Fran Dachille", Platform::Chip48, KeyMap {
+        left: Some(0x5),
+        right: Some(0x6),
+        down: Some(0x7),
+        a: Some(0x4),
+        ..KeyMap::NONE
+    }),
+    entry("429d455a4bc53167942bf6fd934d72b0f648dce3", "Tic-Tac-Toe", "David Winter", Platform::Chip48, KeyMap::NONE),
+    entry("bdb92475acfe11bc7814a2f5eade13fcd09b756a", "UFO", "Lutz V", Platform::Chip48, KeyMap {
+        left: Some(0x4),
+        right: Some(0x6),
+        a: Some(0x5),
+        ..KeyMap::NONE
+    }),
+    entry("da710f631f8e35534d0b9170bcf892a60f49c43d", "Vertical Brix", "Paul Robson", Platform::Chip48, KeyMap {
+        up: Some(0x1),
+        down: Some(0x4),
+        b: Some(0x7),
+        ..KeyMap::NONE
+    }),
+    entry("ade839585ddeb0e3633177df03c1d91589e629eb", "Vers", "JMN", Platform::Chip48, KEYS_PONG),
+    entry("d666688a8fce468a7d88b536bc1ef5f35ba12031", "Wipe Off", "Joseph Weisbecker", Platform::Vip, KEYS_456),
+];
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha1_vectors() {
+        assert_eq!(sha1(b""), hash("da39a3ee5e6b4b0d3255bfef95601890afd80709"));
+        assert_eq!(sha1(b"abc"), hash("a9993e364706816aba3e25717850c26c9cd0d89d"));
+        // Padding that needs a second block.
+        assert_eq!(
+            sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            hash("84983e441c3bd26ebaae4aa1f95129e5e54670f1")
+        );
+    }
+
+    #[test]
+    fn lookup_games() {
+        let brix = lookup(include_bytes!("../games/BRIX")).unwrap();
+        assert_eq!(brix.title, "Brix");
+        assert_eq!(brix.ticks_per_frame(), 10);
+        assert_eq!(brix.keys.left, Some(0x4));
+
+        let kaleid = lookup(include_bytes!("../games/KALEID")).unwrap();
+        assert_eq!(kaleid.platform, Platform::Vip);
+        assert!(kaleid.quirks.display_wait);
+
+        assert!(lookup(&[0x12, 0x00]).is_none());
+    }
+
+    #[test]
+    fn platform_quirks() {
+        let hash = "0000000000000000000000000000000000000000";
+        assert!(entry(hash, "", "", Platform::Vip, KeyMap::NONE).quirks.display_wait);
+
+        let chip48 = entry(hash, "", "", Platform::Chip48, KeyMap::NONE).quirks;
+        assert_eq!(chip48.sprite, Quirks::CHIP8.sprite);
+        assert!(chip48.load_store_increment);
+
+        let schip = entry(hash, "", "", Platform::Schip, KeyMap::NONE).quirks;
+        assert_eq!(schip.sprite, SpriteMode::SchipRows);
+        assert!(!schip.load_store_increment);
+    }
+
+    #[test]
+    fn unique_hashes() {
+        for (i, info) in entries().iter().enumerate() {
+            assert!(entries()[i + 1..].iter().all(|other| other.hash != info.hash), "{}", info.title);
+        }
+    }
+}
@@ -0,0 +1,453 @@
+use crate::chip::{Chip, Scrub};
+use crate::framebuffer::FrameBuffer;
+use crate::{Keypad, Random, KEYPAD_NUM_KEYS, PROGRAM_SIZE};
+
+/// Constant defining the number of actions of an Env: one per key plus NO_ACTION.
+pub const NUM_ACTIONS: usize = KEYPAD_NUM_KEYS + 1;
+
+/// Action that does not press any key.
+pub const NO_ACTION: usize = KEYPAD_NUM_KEYS;
+
+/// Reward trait used by Env to compute the reward of every step.
+///
+/// It is implemented for any `FnMut(&[u8]) -> f32` closure.
+pub trait Reward {
+    /// This function returns the reward of the last step.
+    ///
+    /// # Parameters
+    /// * memory - Chip8 VM memory after the step.
+    fn reward(&mut self, memory: &[u8]) -> f32;
+
+    /// This function is called when the environment is reset, so any state can be cleared.
+    fn reset(&mut self) {}
+}
+
+impl<F: FnMut(&[u8]) -> f32> Reward for F {
+    fn reward(&mut self, memory: &[u8]) -> f32 {
+        self(memory)
+    }
+}
+
+/// Termination trait used by Env to decide if an episode is finished.
+///
+/// It is implemented for any `FnMut(&[u8]) -> bool` closure.
+pub trait Termination {
+    /// This function returns true if the episode is finished.
+    ///
+    /// # Parameters
+    /// * memory - Chip8 VM memory after the step.
+    fn done(&mut self, memory: &[u8]) -> bool;
+}
+
+impl<F: FnMut(&[u8]) -> bool> Termination for F {
+    fn done(&mut self, memory: &[u8]) -> bool {
+        self(memory)
+    }
+}
+
+/// Reward that is always 0.
+pub struct NoReward;
+
+impl Reward for NoReward {
+    fn reward(&mut self, _memory: &[u8]) -> f32 {
+        0.0
+    }
+}
+
+/// Termination that never finishes the episode. Episodes still finish when the program stops
+/// or the frame limit is reached.
+pub struct Never;
+
+impl Termination for Never {
+    fn done(&mut self, _memory: &[u8]) -> bool {
+        false
+    }
+}
+
+/// Reward given by the increase of a score stored in memory as BCD digits, as written by the
+/// `LD B, Vx` instruction.
+///
+/// For example, games/BRIX stores its score at 0x314 with 3 digits.
+pub struct ScoreReward {
+    /// Address of the most significant digit.
+    address: usize,
+
+    /// Number of BCD digits.
+    digits: usize,
+
+    /// Score at the end of the previous step.
+    previous: u32,
+}
+
+impl ScoreReward {
+    /// Creates a new score reward.
+    ///
+    /// # Parameters
+    /// * address - Address of the most significant digit.
+    /// * digits - Number of BCD digits.
+    pub fn new(address: usize, digits: usize) -> Self {
+        ScoreReward {
+            address,
+            digits,
+            previous: 0,
+        }
+    }
+
+    /// Returns the score stored in memory.
+    ///
+    /// # Parameters
+    /// * memory - Chip8 VM memory.
+    pub fn score(&self, memory: &[u8]) -> u32 {
+        memory[self.address..(self.address + self.digits)]
+            .iter()
+            .fold(0, |score, digit| score * 10 + *digit as u32)
+    }
+}
+
+impl Reward for ScoreReward {
+    fn reward(&mut self, memory: &[u8]) -> f32 {
+        let score = self.score(memory);
+        let reward = score as f32 - self.previous as f32;
+        self.previous = score;
+        reward
+    }
+
+    fn reset(&mut self) {
+        self.previous = 0;
+    }
+}
+
+/// Value mixed with the seeds of SeededRandom.
+const SEED_MASK: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Xorshift random number generator, so executions with the same seed are reproducible.
+pub struct SeededRandom {
+    state: u64,
+}
+
+impl SeededRandom {
+    /// Creates a new generator.
+    ///
+    /// # Parameters
+    /// * seed - Seed of the generator. Any value is valid.
+    pub fn new(seed: u64) -> Self {
+        // Avoid the all zeros state, which only produces zeros.
+        let state = seed ^ SEED_MASK;
+        SeededRandom {
+            state: if state == 0 { SEED_MASK } else { state },
+        }
+    }
+}
+
+impl Random for SeededRandom {
+    fn range(&mut self) -> u8 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 56) as u8
+    }
+}
+
+/// Keypad holding the key selected by the current action.
+struct ActionKeypad {
+    key: Option<u8>,
+}
+
+impl Keypad for ActionKeypad {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.key == Some(keycode)
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        self.key
+    }
+}
+
+/// Execution settings of an Env.
+#[derive(Copy, Clone, Debug)]
+pub struct EnvConfig {
+    /// Number of 60Hz frames executed by every step, keeping the action key pressed.
+    pub frame_skip: u32,
+
+    /// Number of instructions executed per frame.
+    pub ticks_per_frame: u32,
+
+    /// Maximum number of frames of an episode. Zero means no limit.
+    pub max_frames: u64,
+}
+
+/// Default implementation for EnvConfig structure.
+///
+/// Steps of 4 frames, 10 instructions per frame (600Hz) and no frame limit.
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            frame_skip: 4,
+            ticks_per_frame: 10,
+            max_frames: 0,
+        }
+    }
+}
+
+/// Result of an Env step.
+pub struct Step<'a> {
+    /// Screen after the step.
+    pub observation: &'a FrameBuffer,
+
+    /// Reward of the step.
+    pub reward: f32,
+
+    /// True if the episode is finished, because it terminated or it was truncated.
+    pub done: bool,
+
+    /// True if the episode reached a terminal state: the termination condition is met or the
+    /// program stopped.
+    pub terminated: bool,
+
+    /// True if the episode was cut at the maximum number of frames.
+    pub truncated: bool,
+}
+
+/// Environment wrapping a Chip8 program with a gym-like API, so agents can be trained and
+/// benchmarked deterministically.
+///
+/// Actions are keys from 0x0 to 0xF, or NO_ACTION, and observations are the packed
+/// framebuffer. The program can be borrowed, like a slice, or owned, like a vector.
+///
+/// # Example
+///
+/// ```
+/// use chip8vm::env::{Env, EnvConfig, Never, ScoreReward, NO_ACTION};
+///
+/// // Program storing the BCD of 42 at 0x300 and stopping.
+/// let rom = [0x60, 0x2A, 0xA3, 0x00, 0xF0, 0x33, 0x12, 0x06];
+/// let mut env = Env::new(&rom, 1, EnvConfig::default(), ScoreReward::new(0x300, 3), Never).unwrap();
+///
+/// let step = env.step(NO_ACTION);
+/// assert_eq!(step.reward, 42.0);
+/// ```
+pub struct Env<P: AsRef<[u8]>, R: Reward, T: Termination> {
+    rom: P,
+    seed: u64,
+    config: EnvConfig,
+    chip: Chip,
+    random: SeededRandom,
+    screen: FrameBuffer,
+    keypad: ActionKeypad,
+    reward: R,
+    termination: T,
+    frames: u64,
+    halted: bool,
+}
+
+impl<P: AsRef<[u8]>, R: Reward, T: Termination> Env<P, R, T> {
+    /// Creates a new environment, ready to run the first episode.
+    ///
+    /// # Parameters
+    /// * rom - Program to run.
+    /// * seed - Seed of the random number generator.
+    /// * config - Execution settings.
+    /// * reward - Reward function.
+    /// * termination - Termination function.
+    ///
+    /// # Return
+    /// * None - If the program does not fit in the Chip8 VM memory.
+    pub fn new(rom: P, seed: u64, config: EnvConfig, reward: R, termination: T) -> Option<Self> {
+        if rom.as_ref().len() > PROGRAM_SIZE {
+            return None;
+        }
+
+        let mut env = Env {
+            rom,
+            seed,
+            config,
+            chip: Chip::default(),
+            random: SeededRandom::new(seed),
+            screen: FrameBuffer::default(),
+            keypad: ActionKeypad { key: None },
+            reward,
+            termination,
+            frames: 0,
+            halted: false,
+        };
+        env.reset();
+        Some(env)
+    }
+
+    /// Restarts the program with the current seed and returns the first observation.
+    pub fn reset(&mut self) -> &FrameBuffer {
+        self.chip.reload(self.rom.as_ref(), Scrub::Zeros, &mut self.random);
+        self.random = SeededRandom::new(self.seed);
+        self.screen = FrameBuffer::default();
+        self.keypad.key = None;
+        self.reward.reset();
+        self.frames = 0;
+        self.halted = false;
+        &self.screen
+    }
+
+    /// Changes the seed and restarts the program, returning the first observation.
+    ///
+    /// # Parameters
+    /// * seed - New seed of the random number generator.
+    pub fn reset_with_seed(&mut self, seed: u64) -> &FrameBuffer {
+        self.seed = seed;
+        self.reset()
+    }
+
+    /// Runs frame_skip frames keeping the action key pressed.
+    ///
+    /// # Parameters
+    /// * action - Key to press, from 0x0 to 0xF, or NO_ACTION.
+    pub fn step(&mut self, action: usize) -> Step<'_> {
+        self.keypad.key = if action < KEYPAD_NUM_KEYS { Some(action as u8) } else { None };
+
+        for _frame in 0..self.config.frame_skip {
+            if self.halted || self.truncated() {
+                break;
+            }
+            for _tick in 0..self.config.ticks_per_frame {
+                if !self.chip.tick(&mut self.random, &mut self.screen, &self.keypad) {
+                    self.halted = true;
+                    break;
+                }
+            }
+            if self.halted {
+                break;
+            }
+            self.chip.tick_timers();
+            self.frames += 1;
+        }
+
+        let memory = self.chip.memory();
+        let reward = self.reward.reward(memory);
+        let terminated = self.halted || self.termination.done(memory);
+        let truncated = self.truncated();
+
+        Step {
+            observation: &self.screen,
+            reward,
+            done: terminated || truncated,
+            terminated,
+            truncated,
+        }
+    }
+
+    /// Returns the current screen.
+    pub fn observation(&self) -> &FrameBuffer {
+        &self.screen
+    }
+
+    /// Returns the Chip8 VM memory.
+    pub fn memory(&self) -> &[u8] {
+        self.chip.memory()
+    }
+
+    /// Returns the number of frames executed in the current episode.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    fn truncated(&self) -> bool {
+        self.config.max_frames > 0 && self.frames >= self.config.max_frames
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRIX: &[u8] = include_bytes!("../games/BRIX");
+    const BRIX_SCORE: usize = 0x314;
+
+    #[test]
+    fn deterministic_episodes() {
+        let config = EnvConfig::default();
+        let mut first = Env::new(BRIX, 3, config, NoReward, Never).unwrap();
+        let mut second = Env::new(BRIX, 3, config, NoReward, Never).unwrap();
+
+        for step in 0..200 {
+            let action = [0x4, 0x6, NO_ACTION][step % 3];
+            let observation = *first.step(action).observation;
+            assert_eq!(&observation, second.step(action).observation);
+        }
+
+        let observation = *first.observation();
+        first.reset();
+        for step in 0..200 {
+            first.step([0x4, 0x6, NO_ACTION][step % 3]);
+        }
+        assert_eq!(&observation, first.observation());
+    }
+
+    #[test]
+    fn brix_score_reward() {
+        let mut env = Env::new(BRIX, 1, EnvConfig::default(), ScoreReward::new(BRIX_SCORE, 3), Never).unwrap();
+
+        let mut total = 0.0;
+        for _step in 0..300 {
+            total += env.step(NO_ACTION).reward;
+        }
+
+        assert!(total > 0.0);
+        assert_eq!(total, ScoreReward::new(BRIX_SCORE, 3).score(env.memory()) as f32);
+    }
+
+    #[test]
+    fn termination_and_truncation() {
+        let config = EnvConfig {
+            frame_skip: 2,
+            max_frames: 6,
+            ..EnvConfig::default()
+        };
+        let mut env = Env::new(BRIX, 1, config, NoReward, |memory: &[u8]| memory[BRIX_SCORE + 2] >= 5).unwrap();
+
+        assert!(!env.step(NO_ACTION).done);
+        assert!(!env.step(NO_ACTION).done);
+        let step = env.step(NO_ACTION);
+        assert!(step.done && step.truncated && !step.terminated);
+        assert_eq!(env.frames(), 6);
+
+        // LD I, 0x300; ADD V0, 1; LD [I], V0; JP 0x200, counting frames at 0x300.
+        let rom = [0xA3, 0x00, 0x70, 0x01, 0xF0, 0x55, 0x12, 0x00];
+        let config = EnvConfig {
+            frame_skip: 1,
+            ticks_per_frame: 4,
+            max_frames: 100,
+        };
+        let mut env = Env::new(&rom, 1, config, NoReward, |memory: &[u8]| memory[0x300] >= 5).unwrap();
+
+        for _step in 0..4 {
+            assert!(!env.step(NO_ACTION).done);
+        }
+        let step = env.step(NO_ACTION);
+        assert!(step.done && step.terminated && !step.truncated);
+        assert_eq!(env.frames(), 5);
+    }
+
+    #[test]
+    fn program_stop_finishes_episode() {
+        let rom = [0x60, 0x01, 0x00, 0x00];
+        let mut env = Env::new(&rom, 1, EnvConfig::default(), NoReward, Never).unwrap();
+
+        assert!(env.step(0x1).done);
+        assert_eq!(env.frames(), 0);
+    }
+
+    #[test]
+    fn any_seed_is_valid() {
+        let mut random = SeededRandom::new(SEED_MASK);
+
+        assert!((0..8).any(|_| random.range() != 0));
+    }
+
+    #[test]
+    fn rom_too_big() {
+        let rom = [0; PROGRAM_SIZE + 1];
+
+        assert!(Env::new(&rom, 1, EnvConfig::default(), NoReward, Never).is_none());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::framebuffer::FrameBuffer;
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Maximum number of frames that can be accumulated by the Accumulate persistence mode.
+pub const MAX_PERSISTENCE_FRAMES: usize = 8;
+
+/// Intensity of a fully lit pixel.
+pub const FULL_INTENSITY: u8 = 255;
+
+/// Persistence modes supported by the PersistenceFilter.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Persistence {
+    /// No persistence. Pixels are fully lit while on and dark while off.
+    None,
+
+    /// Phosphor fade. Pixels that turn off keep their intensity scaled by `factor / 256` on
+    /// every frame, simulating the afterglow of a CRT.
+    Decay { factor: u8 },
+
+    /// Pixels stay fully lit while they have been on in any of the last `frames` frames.
+    /// The number of frames is limited to MAX_PERSISTENCE_FRAMES.
+    Accumulate { frames: usize },
+}
+
+/// Display filter that reduces the flickering produced by programs that erase and redraw
+/// sprites every frame.
+///
+/// The filter must be applied once per displayed frame, usually at the same 60Hz cadence as
+/// the Chip8 VM timers, and produces an intensity value per pixel that frontends can use
+/// when rendering.
+pub struct PersistenceFilter {
+    /// Active persistence mode.
+    mode: Persistence,
+
+    /// Resulting intensity of every pixel, row by row.
+    intensity: [u8; SCREEN_WIDTH * SCREEN_HEIGHT],
+
+    /// Ring buffer with the last frames, used by the Accumulate mode.
+    history: [FrameBuffer; MAX_PERSISTENCE_FRAMES],
+
+    /// Position in the history of the next frame to store.
+    head: usize,
+}
+
+impl PersistenceFilter {
+    /// Creates a new filter with the specified persistence mode.
+    ///
+    /// # Parameters
+    /// * mode - Persistence mode of the filter.
+    pub fn new(mode: Persistence) -> Self {
+        PersistenceFilter {
+            mode,
+            intensity: [0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            history: [FrameBuffer::default(); MAX_PERSISTENCE_FRAMES],
+            head: 0,
+        }
+    }
+
+    /// Returns the active persistence mode.
+    pub fn mode(&self) -> Persistence {
+        self.mode
+    }
+
+    /// Changes the persistence mode, discarding the accumulated frames.
+    ///
+    /// # Parameters
+    /// * mode - New persistence mode of the filter.
+    pub fn set_mode(&mut self, mode: Persistence) {
+        *self = PersistenceFilter::new(mode);
+    }
+
+    /// Blends the provided frame with the previous ones, updating the pixel intensities.
+    ///
+    /// # Parameters
+    /// * frame - Current content of the screen.
+    pub fn apply(&mut self, frame: &FrameBuffer) {
+        self.history[self.head] = *frame;
+        self.head = (self.head + 1) % MAX_PERSISTENCE_FRAMES;
+
+        for y in 0..SCREEN_HEIGHT {
+            for x in 0..SCREEN_WIDTH {
+                let position = y * SCREEN_WIDTH + x;
+                let previous = self.intensity[position];
+                self.intensity[position] = if frame.is_on(x, y) {
+                    FULL_INTENSITY
+                } else {
+                    match self.mode {
+                        Persistence::None => 0,
+                        Persistence::Decay { factor } => {
+                            ((previous as u16 * factor as u16) >> 8) as u8
+                        }
+                        Persistence::Accumulate { frames } => {
+                            if self.accumulated(x, y, frames) {
+                                FULL_INTENSITY
+                            } else {
+                                0
+                            }
+                        }
+                    }
+                };
+            }
+        }
+    }
+
+    /// Returns the intensity of the pixel in the (x, y) coordinates. From 0 (off) to
+    /// FULL_INTENSITY.
+    ///
+    /// # Parameters
+    /// * x - Coordinate x of the pixel.
+    /// * y - Coordinate y of the pixel.
+    pub fn intensity(&self, x: usize, y: usize) -> u8 {
+        self.intensity[y * SCREEN_WIDTH + x]
+    }
+
+    /// Returns the intensities of all the pixels, row by row.
+    pub fn intensities(&self) -> &[u8; SCREEN_WIDTH * SCREEN_HEIGHT] {
+        &self.intensity
+    }
+
+    fn accumulated(&self, x: usize, y: usize, frames: usize) -> bool {
+        let frames = frames.min(MAX_PERSISTENCE_FRAMES);
+        (1..=frames).any(|age| {
+            let index = (self.head + MAX_PERSISTENCE_FRAMES - age) % MAX_PERSISTENCE_FRAMES;
+            self.history[index].is_on(x, y)
+        })
+    }
+}
+
+/// Default implementation for PersistenceFilter structure.
+///
+/// Initializes a filter without persistence.
+impl Default for PersistenceFilter {
+    fn default() -> Self {
+        PersistenceFilter::new(Persistence::None)
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Screen;
+
+    #[test]
+    fn no_persistence() {
+        let mut filter = PersistenceFilter::default();
+        let mut frame = FrameBuffer::default();
+        frame.draw(1, 1);
+        filter.apply(&frame);
+
+        frame.clear();
+        filter.apply(&frame);
+
+        assert_eq!(filter.intensity(1, 1), 0);
+    }
+
+    #[test]
+    fn decay_persistence() {
+        let mut filter = PersistenceFilter::new(Persistence::Decay { factor: 128 });
+        let mut frame = FrameBuffer::default();
+        frame.draw(1, 1);
+        filter.apply(&frame);
+        assert_eq!(filter.intensity(1, 1), FULL_INTENSITY);
+
+        frame.clear();
+        filter.apply(&frame);
+        assert_eq!(filter.intensity(1, 1), 127);
+
+        filter.apply(&frame);
+        assert_eq!(filter.intensity(1, 1), 63);
+    }
+
+    #[test]
+    fn accumulate_persistence() {
+        let mut filter = PersistenceFilter::new(Persistence::Accumulate { frames: 2 });
+        let mut frame = FrameBuffer::default();
+        frame.draw(1, 1);
+        filter.apply(&frame);
+
+        frame.clear();
+        filter.apply(&frame);
+        assert_eq!(filter.intensity(1, 1), FULL_INTENSITY);
+
+        filter.apply(&frame);
+        assert_eq!(filter.intensity(1, 1), 0);
+    }
+}
@@ -1,111 +1,169 @@
+/// Constant defining the number of characters of a font. From 0x0 to 0xF.
 pub const CHARACTERS: usize = 16;
+/// Constant defining the size in bytes of a small font character, 8x5 pixels.
 pub const CHARACTER_SIZE: usize = 5;
-
+/// Constant defining the size in bytes of a big font character, 8x10 pixels.
+pub const BIG_CHARACTER_SIZE: usize = 10;
+
+/// Constant defining the size in bytes of a small font.
+pub const FONT_SIZE: usize = CHARACTER_SIZE * CHARACTERS;
+/// Constant defining the size in bytes of a big font.
+pub const BIG_FONT_SIZE: usize = BIG_CHARACTER_SIZE * CHARACTERS;
+
+/// Font structure with the character sprites loaded in the interpreter memory.
+///
+/// The small font is used by the FX29 instruction. The optional big font is loaded right after
+/// the small font and is used by the SCHIP FX30 instruction.
 pub struct Font {
-    pub set: [u8; CHARACTER_SIZE * CHARACTERS],
-}
-
-impl Default for Font {
-    fn default() -> Font {
-        Font {
-            #[rustfmt::skip]
-            set: [
-                0b_11110000,
-                0b_10010000,
-                0b_10010000,
-                0b_10010000,
-                0b_11110000, // 0
-
-                0b_00100000,
-                0b_01100000,
-                0b_00100000,
-                0b_00100000,
-                0b_01110000, // 1
-
-                0b_11110000,
-                0b_00010000,
-                0b_11110000,
-                0b_10000000,
-                0b_11110000, // 2
-
-                0b_11110000,
-                0b_00010000,
-                0b_11110000,
-                0b_00010000,
-                0b_11110000, // 3
-
-                0b_10010000,
-                0b_10010000,
-                0b_11110000,
-                0b_00010000,
-                0b_00010000, // 4
-
-                0b_11110000,
-                0b_10000000,
-                0b_11110000,
-                0b_00010000,
-                0b_11110000, // 5
-
-                0b_11110000,
-                0b_10000000,
-                0b_11110000,
-                0b_10010000,
-                0b_11110000, // 6
-
-                0b_11110000,
-                0b_00010000,
-                0b_00100000,
-                0b_01000000,
-                0b_01000000, // 7
-
-                0b_11110000,
-                0b_10010000,
-                0b_11110000,
-                0b_10010000,
-                0b_11110000, // 8
+    /// Small font, 5 bytes per character.
+    pub set: [u8; FONT_SIZE],
 
-                0b_11110000,
-                0b_10010000,
-                0b_11110000,
-                0b_00010000,
-                0b_11110000, // 9
-
-                0b_11110000,
-                0b_10010000,
-                0b_11110000,
-                0b_10010000,
-                0b_10010000, // A
-
-                0b_11100000,
-                0b_10010000,
-                0b_11100000,
-                0b_10010000,
-                0b_11100000, // B
-
-                0b_11110000,
-                0b_10000000,
-                0b_10000000,
-                0b_10000000,
-                0b_11110000, // C
+    /// Optional big font, 10 bytes per character.
+    pub big: Option<[u8; BIG_FONT_SIZE]>,
+}
 
-                0b_11100000,
-                0b_10010000,
-                0b_10010000,
-                0b_10010000,
-                0b_11100000, // D
+impl Font {
+    /// Font used by the Chip8 VM since its first version.
+    #[rustfmt::skip]
+    pub const CHIP8: Font = Font::new([
+        0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+        0x20, 0x60, 0x20, 0x20, 0x70, // 1
+        0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+        0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+        0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+        0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+        0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+        0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+        0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+        0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+        0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+        0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+        0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+        0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+        0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+        0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+    ], None);
+
+    /// Font of the COSMAC VIP interpreter.
+    #[rustfmt::skip]
+    pub const VIP: Font = Font::new([
+        0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+        0x60, 0x20, 0x20, 0x20, 0x70, // 1
+        0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+        0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+        0xA0, 0xA0, 0xF0, 0x20, 0x20, // 4
+        0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+        0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+        0xF0, 0x10, 0x10, 0x10, 0x10, // 7
+        0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+        0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+        0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+        0xF0, 0x50, 0x70, 0x50, 0xF0, // B
+        0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+        0xF0, 0x50, 0x50, 0x50, 0xF0, // D
+        0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+        0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+    ], None);
+
+    /// Font of the DREAM 6800 interpreter, 3 pixels wide.
+    #[rustfmt::skip]
+    pub const DREAM6800: Font = Font::new([
+        0xE0, 0xA0, 0xA0, 0xA0, 0xE0, // 0
+        0x40, 0x40, 0x40, 0x40, 0x40, // 1
+        0xE0, 0x20, 0xE0, 0x80, 0xE0, // 2
+        0xE0, 0x20, 0xE0, 0x20, 0xE0, // 3
+        0x80, 0xA0, 0xA0, 0xE0, 0x20, // 4
+        0xE0, 0x80, 0xE0, 0x20, 0xE0, // 5
+        0xE0, 0x80, 0xE0, 0xA0, 0xE0, // 6
+        0xE0, 0x20, 0x20, 0x20, 0x20, // 7
+        0xE0, 0xA0, 0xE0, 0xA0, 0xE0, // 8
+        0xE0, 0xA0, 0xE0, 0x20, 0xE0, // 9
+        0xE0, 0xA0, 0xE0, 0xA0, 0xA0, // A
+        0xC0, 0xA0, 0xE0, 0xA0, 0xC0, // B
+        0xE0, 0x80, 0x80, 0x80, 0xE0, // C
+        0xC0, 0xA0, 0xA0, 0xA0, 0xC0, // D
+        0xE0, 0x80, 0xE0, 0x80, 0xE0, // E
+        0xE0, 0x80, 0xC0, 0x80, 0x80, // F
+    ], None);
+
+    /// Font of the ETI-660 interpreter, 3 pixels wide.
+    #[rustfmt::skip]
+    pub const ETI660: Font = Font::new([
+        0xE0, 0xA0, 0xA0, 0xA0, 0xE0, // 0
+        0x20, 0x20, 0x20, 0x20, 0x20, // 1
+        0xE0, 0x20, 0xE0, 0x80, 0xE0, // 2
+        0xE0, 0x20, 0xE0, 0x20, 0xE0, // 3
+        0xA0, 0xA0, 0xE0, 0x20, 0x20, // 4
+        0xE0, 0x80, 0xE0, 0x20, 0xE0, // 5
+        0xE0, 0x80, 0xE0, 0xA0, 0xE0, // 6
+        0xE0, 0x20, 0x20, 0x20, 0x20, // 7
+        0xE0, 0xA0, 0xE0, 0xA0, 0xE0, // 8
+        0xE0, 0xA0, 0xE0, 0x20, 0xE0, // 9
+        0xE0, 0xA0, 0xE0, 0xA0, 0xA0, // A
+        0x80, 0x80, 0xE0, 0xA0, 0xE0, // B
+        0xE0, 0x80, 0x80, 0x80, 0xE0, // C
+        0x20, 0x20, 0xE0, 0xA0, 0xE0, // D
+        0xE0, 0x80, 0xE0, 0x80, 0xE0, // E
+        0xE0, 0x80, 0xC0, 0x80, 0x80, // F
+    ], None);
+
+    /// Font of the HP48 SCHIP interpreter, with its 8x10 big font. SCHIP only defines the big
+    /// digits, the big letters follow the same style.
+    #[rustfmt::skip]
+    pub const SCHIP: Font = Font::new(Font::CHIP8.set, Some([
+        0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+        0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+        0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+        0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+        0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+        0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+        0x3E, 0x7C, 0xC0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+        0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+        0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+        0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+        0x18, 0x3C, 0x66, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+        0xFC, 0xFE, 0xC3, 0xC3, 0xFE, 0xFE, 0xC3, 0xC3, 0xFE, 0xFC, // B
+        0x3C, 0x7E, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0x7E, 0x3C, // C
+        0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+        0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFC, 0xC0, 0xC0, 0xFF, 0xFF, // E
+        0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFC, 0xC0, 0xC0, 0xC0, 0xC0, // F
+    ]));
+
+    /// Creates a font from its character sprites.
+    ///
+    /// # Parameters
+    /// * set - Small font, 5 bytes per character from 0x0 to 0xF.
+    /// * big - Optional big font, 10 bytes per character from 0x0 to 0xF.
+    pub const fn new(set: [u8; FONT_SIZE], big: Option<[u8; BIG_FONT_SIZE]>) -> Self {
+        Font { set, big }
+    }
 
-                0b_11110000,
-                0b_10000000,
-                0b_11110000,
-                0b_10000000,
-                0b_11110000, // E
+    /// Returns the number of bytes used by the font in memory.
+    pub const fn size(&self) -> usize {
+        match self.big {
+            Some(_) => FONT_SIZE + BIG_FONT_SIZE,
+            None => FONT_SIZE,
+        }
+    }
 
-                0b_11110000,
-                0b_10000000,
-                0b_11110000,
-                0b_10000000,
-                0b_10000000, // F
-            ],
+    /// Returns the byte of the font stored at the offset from the font address, with the big
+    /// font following the small font.
+    ///
+    /// # Parameters
+    /// * offset - Offset from the font address. From 0 to size() - 1.
+    pub fn byte(&self, offset: usize) -> u8 {
+        match &self.big {
+            Some(big) if offset >= FONT_SIZE => big[offset - FONT_SIZE],
+            _ => self.set[offset],
         }
     }
 }
+
+/// Default implementation for Font structure.
+///
+/// Returns the CHIP8 font.
+impl Default for Font {
+    fn default() -> Font {
+        Font::CHIP8
+    }
+}
@@ -0,0 +1,405 @@
+use core::fmt;
+
+use crate::specs::PROG_START;
+
+/// Number of data bytes per Intel HEX record and per hex dump line.
+pub const BYTES_PER_LINE: usize = 16;
+
+/// Number of base64 characters per line.
+pub const BASE64_LINE_SIZE: usize = 76;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Errors of the ROM image importers.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Error {
+    /// The image does not fit in the buffer.
+    Buffer,
+
+    /// Invalid character or record at the provided line, starting at 1.
+    Syntax(usize),
+
+    /// Intel HEX record with a wrong checksum at the provided line.
+    Checksum(usize),
+
+    /// Intel HEX record with data before PROG_START at the provided line.
+    Address(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Buffer => write!(f, "the image does not fit in the buffer"),
+            Error::Syntax(line) => write!(f, "syntax error at line {}", line),
+            Error::Checksum(line) => write!(f, "wrong checksum at line {}", line),
+            Error::Address(line) => write!(f, "data before the program start at line {}", line),
+        }
+    }
+}
+
+/// ROM image formats.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Format {
+    /// Binary data, as loaded in memory.
+    Raw,
+
+    /// Intel HEX records, with the program at its load address, PROG_START. Used by flashing
+    /// tools.
+    IntelHex,
+
+    /// Lines with the address and the hexadecimal values of BYTES_PER_LINE bytes, like
+    /// `0200: 00 E0 A2 2A`. Imports store the values of a line at its address, and also accept
+    /// lines without address nor spaces, continuing after the previous line.
+    HexDump,
+
+    /// Base64 text, as used in web pastes. Imports also accept the URL safe alphabet and
+    /// missing padding.
+    Base64,
+}
+
+impl Format {
+    /// Returns the format of a file extension: `hex`, `ihex` or `ihx` for Intel HEX, `txt` for
+    /// hex dumps, `b64` for base64 and `ch8`, `c8` or `bin` for raw images.
+    ///
+    /// # Parameters
+    /// * extension - File extension, without the dot.
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension {
+            "ch8" | "c8" | "bin" => Some(Format::Raw),
+            "hex" | "ihex" | "ihx" => Some(Format::IntelHex),
+            "txt" => Some(Format::HexDump),
+            "b64" => Some(Format::Base64),
+            _ => None,
+        }
+    }
+
+    /// Decodes an image into the provided buffer.
+    ///
+    /// # Parameters
+    /// * data - Image in this format.
+    /// * buffer - Buffer receiving the program.
+    ///
+    /// # Return
+    /// * Result<usize, Error> - Size of the program, or the error found.
+    pub fn import(self, data: &[u8], buffer: &mut [u8]) -> Result<usize, Error> {
+        match self {
+            Format::Raw => {
+                buffer.get_mut(..data.len()).ok_or(Error::Buffer)?.copy_from_slice(data);
+                Ok(data.len())
+            }
+            Format::IntelHex => import_intel_hex(data, buffer),
+            Format::HexDump => import_hex_dump(data, buffer),
+            Format::Base64 => import_base64(data, buffer),
+        }
+    }
+
+    /// Encodes a program.
+    ///
+    /// # Parameters
+    /// * rom - Program data.
+    /// * output - Output function receiving the encoded data.
+    pub fn export(self, rom: &[u8], output: &mut impl FnMut(&[u8])) {
+        match self {
+            Format::Raw => output(rom),
+            Format::IntelHex => export_intel_hex(rom, output),
+            Format::HexDump => export_hex_dump(rom, output),
+            Format::Base64 => export_base64(rom, output),
+        }
+    }
+}
+
+/// Returns the value of a hexadecimal digit.
+fn hex_value(digit: u8) -> Option<u8> {
+    (digit as char).to_digit(16).map(|value| value as u8)
+}
+
+/// Writes the byte as 2 hexadecimal digits.
+fn hex_byte(byte: u8) -> [u8; 2] {
+    [HEX_DIGITS[(byte >> 4) as usize], HEX_DIGITS[(byte & 0xF) as usize]]
+}
+
+/// Stores a byte of an image in the buffer, growing the size of the image.
+fn store(buffer: &mut [u8], size: &mut usize, offset: usize, byte: u8) -> Result<(), Error> {
+    *buffer.get_mut(offset).ok_or(Error::Buffer)? = byte;
+    *size = (*size).max(offset + 1);
+    Ok(())
+}
+
+/// Stores a byte at the end of an image.
+fn append(buffer: &mut [u8], size: &mut usize, byte: u8) -> Result<(), Error> {
+    store(buffer, size, *size, byte)
+}
+
+fn export_intel_hex(rom: &[u8], output: &mut impl FnMut(&[u8])) {
+    let mut record = [0u8; 11 + BYTES_PER_LINE * 2 + 1];
+    let mut write = |kind: u8, address: u16, data: &[u8]| {
+        let [high, low] = address.to_be_bytes();
+        let header = [data.len() as u8, high, low, kind];
+        let checksum = header.iter().chain(data.iter()).fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+
+        let mut length = 0;
+        for byte in header.iter().chain(data.iter()).chain([checksum.wrapping_neg()].iter()) {
+            record[length..length + 2].copy_from_slice(&hex_byte(*byte));
+            length += 2;
+        }
+        output(b":");
+        output(&record[..length]);
+        output(b"\n");
+    };
+
+    // Programs never cross 64 KiB, so extended address records are not needed.
+    for (line, data) in rom.chunks(BYTES_PER_LINE).enumerate() {
+        write(0x00, (PROG_START + line * BYTES_PER_LINE) as u16, data);
+    }
+    write(0x01, 0, &[]);
+}
+
+fn import_intel_hex(data: &[u8], buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut size = 0;
+    let mut base = 0;
+    for (index, line) in data.split(|byte| *byte == b'\n').enumerate() {
+        let number = index + 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.iter().all(|byte| byte.is_ascii_whitespace()) {
+            continue;
+        }
+
+        let digits = line.strip_prefix(b":").ok_or(Error::Syntax(number))?;
+        let mut record = [0u8; 5 + 255];
+        if digits.len() % 2 != 0 || digits.len() < 10 || digits.len() / 2 > record.len() {
+            return Err(Error::Syntax(number));
+        }
+        let bytes = &mut record[..digits.len() / 2];
+        for (byte, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+            *byte = hex_value(pair[0]).zip(hex_value(pair[1])).map(|(high, low)| high << 4 | low).ok_or(Error::Syntax(number))?;
+        }
+        if bytes.len() != 5 + bytes[0] as usize {
+            return Err(Error::Syntax(number));
+        }
+        if bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            return Err(Error::Checksum(number));
+        }
+        let payload = &bytes[4..bytes.len() - 1];
+
+        match bytes[3] {
+            0x00 => {
+                let address = base + u16::from_be_bytes([bytes[1], bytes[2]]) as usize;
+                let offset = address.checked_sub(PROG_START).ok_or(Error::Address(number))?;
+                for (position, byte) in payload.iter().enumerate() {
+                    store(buffer, &mut size, offset + position, *byte)?;
+                }
+            }
+            0x01 => return Ok(size),
+            0x02 if payload.len() == 2 => base = (u16::from_be_bytes([payload[0], payload[1]]) as usize) << 4,
+            0x04 if payload.len() == 2 => base = (u16::from_be_bytes([payload[0], payload[1]]) as usize) << 16,
+            0x03 | 0x05 => {}
+            _ => return Err(Error::Syntax(number)),
+        }
+    }
+    Ok(size)
+}
+
+fn export_hex_dump(rom: &[u8], output: &mut impl FnMut(&[u8])) {
+    for (line, data) in rom.chunks(BYTES_PER_LINE).enumerate() {
+        let address = ((PROG_START + line * BYTES_PER_LINE) as u16).to_be_bytes();
+        let mut text = [b' '; 6 + BYTES_PER_LINE * 3];
+        text[..2].copy_from_slice(&hex_byte(address[0]));
+        text[2..4].copy_from_slice(&hex_byte(address[1]));
+        text[4] = b':';
+        for (position, byte) in data.iter().enumerate() {
+            text[6 + position * 3..8 + position * 3].copy_from_slice(&hex_byte(*byte));
+        }
+        let length = 6 + data.len() * 3 - 1;
+        text[length] = b'\n';
+        output(&text[..length + 1]);
+    }
+}
+
+fn import_hex_dump(data: &[u8], buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut size = 0;
+    let mut offset = 0;
+    for (index, line) in data.split(|byte| *byte == b'\n').enumerate() {
+        let number = index + 1;
+        let values = match line.iter().position(|byte| *byte == b':') {
+            Some(colon) => {
+                let digits = line[..colon].iter().filter(|byte| !byte.is_ascii_whitespace());
+                if !(1..=4).contains(&digits.clone().count()) {
+                    return Err(Error::Syntax(number));
+                }
+                let address = digits.map(|byte| hex_value(*byte)).try_fold(0, |address, value| Some(address << 4 | value? as usize));
+                let address = address.ok_or(Error::Syntax(number))?;
+                offset = address.checked_sub(PROG_START).ok_or(Error::Address(number))?;
+                &line[colon + 1..]
+            }
+            None => line,
+        };
+
+        let mut high = None;
+        for byte in values.iter().filter(|byte| !byte.is_ascii_whitespace()) {
+            let value = hex_value(*byte).ok_or(Error::Syntax(number))?;
+            match high.take() {
+                None => high = Some(value),
+                Some(high) => {
+                    store(buffer, &mut size, offset, high << 4 | value)?;
+                    offset += 1;
+                }
+            }
+        }
+        if high.is_some() {
+            return Err(Error::Syntax(number));
+        }
+    }
+    Ok(size)
+}
+
+fn export_base64(rom: &[u8], output: &mut impl FnMut(&[u8])) {
+    let mut line = [0u8; BASE64_LINE_SIZE + 1];
+    let mut length = 0;
+    for chunk in rom.chunks(3) {
+        let bits = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for position in 0..4 {
+            line[length + position] = if position <= chunk.len() {
+                BASE64_ALPHABET[(bits >> (18 - position * 6) & 0x3F) as usize]
+            } else {
+                b'='
+            };
+        }
+        length += 4;
+        if length == BASE64_LINE_SIZE {
+            line[length] = b'\n';
+            output(&line);
+            length = 0;
+        }
+    }
+    if length > 0 {
+        line[length] = b'\n';
+        output(&line[..length + 1]);
+    }
+}
+
+fn import_base64(data: &[u8], buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut size = 0;
+    let mut bits = 0u32;
+    let mut count = 0;
+    let mut padding = false;
+    let mut line = 1;
+    for byte in data.iter() {
+        let value = match *byte {
+            b'\n' => {
+                line += 1;
+                continue;
+            }
+            _ if byte.is_ascii_whitespace() => continue,
+            b'=' => {
+                padding = true;
+                continue;
+            }
+            _ if padding => return Err(Error::Syntax(line)),
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(Error::Syntax(line)),
+        };
+
+        bits = bits << 6 | value as u32;
+        count += 1;
+        if count == 4 {
+            for shift in [16, 8, 0].iter() {
+                append(buffer, &mut size, (bits >> shift) as u8)?;
+            }
+            bits = 0;
+            count = 0;
+        }
+    }
+
+    // A final group of 2 or 3 characters carries 1 or 2 bytes.
+    match count {
+        0 => {}
+        2 => append(buffer, &mut size, (bits >> 4) as u8)?,
+        3 => {
+            append(buffer, &mut size, (bits >> 10) as u8)?;
+            append(buffer, &mut size, (bits >> 2) as u8)?;
+        }
+        _ => return Err(Error::Syntax(line)),
+    }
+    Ok(size)
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::PROGRAM_SIZE;
+    use std::vec::Vec;
+
+    const FORMATS: [Format; 4] = [Format::Raw, Format::IntelHex, Format::HexDump, Format::Base64];
+
+    fn export(format: Format, rom: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        format.export(rom, &mut |bytes: &[u8]| data.extend_from_slice(bytes));
+        data
+    }
+
+    fn import(format: Format, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut buffer = [0; PROGRAM_SIZE];
+        let size = format.import(data, &mut buffer)?;
+        Ok(buffer[..size].to_vec())
+    }
+
+    #[test]
+    fn round_trips() {
+        let game = &include_bytes!("../games/BLINKY")[..];
+        for format in FORMATS.iter() {
+            for length in [0, 1, 2, 3, 15, 16, 17, 57, 58, game.len()].iter() {
+                let rom = &game[..*length];
+                assert_eq!(import(*format, &export(*format, rom)).unwrap(), rom, "{:?} {}", format, length);
+            }
+        }
+    }
+
+    #[test]
+    fn exported_text() {
+        let rom = [0x00, 0xE0, 0xA2, 0x2A, 0x60, 0x0C];
+        assert_eq!(export(Format::IntelHex, &rom), b":0602000000E0A22A600CE0\n:00000001FF\n");
+        assert_eq!(export(Format::HexDump, &rom), b"0200: 00 E0 A2 2A 60 0C\n");
+        assert_eq!(export(Format::Base64, &rom), b"AOCiKmAM\n");
+        assert_eq!(export(Format::Base64, &rom[..4]), b"AOCiKg==\n");
+    }
+
+    #[test]
+    fn lenient_imports() {
+        assert_eq!(import(Format::HexDump, b"00e0a22a\n 600C ").unwrap(), [0x00, 0xE0, 0xA2, 0x2A, 0x60, 0x0C]);
+        assert_eq!(import(Format::HexDump, b"0204: 60 0C\n0200: 00 E0\nA2 2A").unwrap(), [0x00, 0xE0, 0xA2, 0x2A, 0x60, 0x0C]);
+        assert_eq!(import(Format::HexDump, b"0204: 60 0C\n").unwrap(), [0x00, 0x00, 0x00, 0x00, 0x60, 0x0C]);
+        assert_eq!(import(Format::Base64, b"AOCi\r\nKg").unwrap(), [0x00, 0xE0, 0xA2, 0x2A]);
+        assert_eq!(import(Format::Base64, b"-_8=").unwrap(), [0xFB, 0xFF]);
+        // Extended segment address and out of order records.
+        let hex = b":020000020020DC\r\n:01000200E01D\n:0100000000FF\n:00000001FF\n";
+        assert_eq!(import(Format::IntelHex, hex).unwrap(), [0x00, 0x00, 0xE0]);
+    }
+
+    #[test]
+    fn import_errors() {
+        assert_eq!(import(Format::IntelHex, b":0602000000E0A22A600C2B\n"), Err(Error::Checksum(1)));
+        assert_eq!(import(Format::IntelHex, b"\n:0601000000E0A22A600C2B\n"), Err(Error::Checksum(2)));
+        assert_eq!(import(Format::IntelHex, b":01010000E01E\n"), Err(Error::Address(1)));
+        assert_eq!(import(Format::IntelHex, b"0602000000E0A22A600CE0\n"), Err(Error::Syntax(1)));
+        assert_eq!(import(Format::IntelHex, &[b":".as_ref(), &[b'0'; 600]].concat()), Err(Error::Syntax(1)));
+        assert_eq!(import(Format::IntelHex, b":0702000000E0A22A600CDF\n"), Err(Error::Syntax(1)));
+        assert_eq!(import(Format::HexDump, b"0200: 00 E\n"), Err(Error::Syntax(1)));
+        assert_eq!(import(Format::HexDump, b"00\n0G\n"), Err(Error::Syntax(2)));
+        assert_eq!(import(Format::HexDump, b"0100: 00 E0\n"), Err(Error::Address(1)));
+        assert_eq!(import(Format::HexDump, b"02G0: 00 E0\n"), Err(Error::Syntax(1)));
+        assert_eq!(import(Format::Base64, b"AOC=i"), Err(Error::Syntax(1)));
+        assert_eq!(import(Format::Base64, b"A"), Err(Error::Syntax(1)));
+        assert_eq!(Format::Raw.import(&[0; 4], &mut [0; 3]), Err(Error::Buffer));
+        assert_eq!(Format::HexDump.import(b"00 01", &mut [0; 1]), Err(Error::Buffer));
+    }
+}
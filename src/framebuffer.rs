@@ -0,0 +1,120 @@
+use crate::{Screen, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Constant defining the size in bytes of a packed Chip8 VM framebuffer.
+pub const FRAMEBUFFER_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT / 8;
+
+/// Monochrome framebuffer implementing the Screen trait.
+///
+/// Pixels are packed one bit per pixel, row by row, with the most significant bit of each byte
+/// being the leftmost pixel. Frontends can use it as the Screen of the Chip8 VM and read it back
+/// when rendering.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FrameBuffer {
+    /// Packed pixel data.
+    data: [u8; FRAMEBUFFER_SIZE],
+}
+
+/// Default implementation for FrameBuffer structure.
+///
+/// Initializes a FrameBuffer with all the pixels off.
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        FrameBuffer {
+            data: [0; FRAMEBUFFER_SIZE],
+        }
+    }
+}
+
+impl FrameBuffer {
+    /// Returns the status of the pixel in the (x, y) coordinates.
+    ///
+    /// # Parameters
+    /// * x - Coordinate x of the pixel. From 0 to SCREEN_WIDTH - 1.
+    /// * y - Coordinate y of the pixel. From 0 to SCREEN_HEIGHT - 1.
+    ///
+    /// # Return
+    /// * true - If the pixel is on.
+    /// * false - If the pixel is off.
+    pub fn is_on(&self, x: usize, y: usize) -> bool {
+        let (byte, mask) = Self::locate(x, y);
+        self.data[byte] & mask != 0
+    }
+
+    /// Sets the status of the pixel in the (x, y) coordinates.
+    ///
+    /// # Parameters
+    /// * x - Coordinate x of the pixel. From 0 to SCREEN_WIDTH - 1.
+    /// * y - Coordinate y of the pixel. From 0 to SCREEN_HEIGHT - 1.
+    /// * on - New status of the pixel.
+    pub fn set(&mut self, x: usize, y: usize, on: bool) {
+        let (byte, mask) = Self::locate(x, y);
+        if on {
+            self.data[byte] |= mask;
+        } else {
+            self.data[byte] &= !mask;
+        }
+    }
+
+    /// Returns the packed pixel data of the framebuffer.
+    pub fn data(&self) -> &[u8; FRAMEBUFFER_SIZE] {
+        &self.data
+    }
+
+    fn locate(x: usize, y: usize) -> (usize, u8) {
+        let position = y * SCREEN_WIDTH + x;
+        (position / 8, 0x80 >> (position % 8))
+    }
+}
+
+impl Screen for FrameBuffer {
+    fn clear(&mut self) {
+        self.data = [0; FRAMEBUFFER_SIZE];
+    }
+
+    fn draw(&mut self, x: u8, y: u8) -> bool {
+        let (byte, mask) = Self::locate(x as usize, y as usize);
+        let collision = self.data[byte] & mask != 0;
+        self.data[byte] ^= mask;
+        collision
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_without_collision() {
+        let mut framebuffer = FrameBuffer::default();
+
+        let collision = framebuffer.draw(3, 2);
+
+        assert!(!collision);
+        assert!(framebuffer.is_on(3, 2));
+        assert_eq!(framebuffer.data()[2 * SCREEN_WIDTH / 8], 0b_00010000);
+    }
+
+    #[test]
+    fn draw_with_collision() {
+        let mut framebuffer = FrameBuffer::default();
+        framebuffer.set(63, 31, true);
+
+        let collision = framebuffer.draw(63, 31);
+
+        assert!(collision);
+        assert!(!framebuffer.is_on(63, 31));
+    }
+
+    #[test]
+    fn clear() {
+        let mut framebuffer = FrameBuffer::default();
+        framebuffer.set(10, 10, true);
+
+        framebuffer.clear();
+
+        assert_eq!(framebuffer, FrameBuffer::default());
+    }
+}
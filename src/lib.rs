@@ -5,13 +5,51 @@
 //!
 //! Trait interfaces:
 //! * Random - Numerical random range interface to provide a random range implementation to
-//! the Chip8 VM.
+//!   the Chip8 VM.
 //! * Screen - Screen interface to provide screen capabilities to the Chip 8 VM.
 //! * Keypad - Kaypad interface to provide input events to the Chip8 VM.
+//! * Bus - Memory access interface used by the Chip8 VM for every read and write. The
+//!   bus::Memory default implementation is backed by an internal array.
+//!
+//! Configuration:
+//! * quirks::Quirks - Behaviours that differ between interpreters, like the font set and its
+//!   address, with the profiles of well known interpreters.
+//! * font::Font - Small and big font sets, with the fonts of well known interpreters.
+//!
+//! Display helpers:
+//! * framebuffer::FrameBuffer - Packed monochrome Screen implementation.
+//! * filter::PersistenceFilter - Phosphor persistence filter to reduce flickering.
+//! * palette::Palette - Color palettes to convert the framebuffer to RGBA.
+//! * capture - PBM and PNG screenshots and animated GIF recording of the framebuffer. The
+//!   encoders write through an output function, so they can be used without allocation.
+//!
+//! Environments:
+//! * env::Env - Gym-like environment running a program with frame skipping, keypad actions,
+//!   framebuffer observations and pluggable reward and termination functions.
+//!
+//! Tools:
+//! * container - Loaders of programs with their configuration from binaries, Octo cartridges
+//!   and JSON or TOML options files.
+//! * database - Metadata of known programs by SHA-1 hash, with the quirks, speed, key map and
+//!   colours they need.
+//! * formats - Import and export of programs as raw, Intel HEX, hex dump and base64 images.
+//! * lint - Static analysis of programs, reporting unreachable code, invalid jumps, stack
+//!   depth problems, self-modifying stores and quirk dependent or undefined instructions.
 
+pub mod bus;
+pub mod capture;
 pub mod chip;
+pub mod container;
+pub mod database;
+pub mod env;
+pub mod filter;
+pub mod font;
+pub mod formats;
+pub mod framebuffer;
+pub mod palette;
+pub mod lint;
+pub mod quirks;
 
-mod font;
 mod specs;
 
 use specs::*;
@@ -19,6 +57,9 @@ use specs::*;
 /// Constant defining Chip8 VM program size.
 pub const PROGRAM_SIZE: usize = PROG_END - PROG_START;
 
+/// Constant defining Chip8 VM default addressable memory size.
+pub const MEMORY_SIZE: usize = MEM_SIZE;
+
 
 /// Random trait used by Chip 8 VM to get random numbers.
 pub trait Random {
@@ -81,3 +122,30 @@ pub trait Keypad {
     /// * Optional u8 - Optional return of a keycode. From 0x0 to 0xF.
     fn pressed_key(&self) -> Option<u8>;
 }
+
+
+/// Bus trait used by the Chip8 VM to access memory: instruction fetches, sprite data and the
+/// loads and stores of the I register instructions.
+///
+/// Implementations can map regions to devices, protect regions from writes, count accesses or
+/// place the program and the variables in different memories.
+pub trait Bus {
+    /// This function reads the byte stored at the address.
+    ///
+    /// # Parameters
+    /// * address - Address to read. From 0x000 to size() - 1.
+    fn read(&mut self, address: u16) -> u8;
+
+    /// This function writes a byte at the address.
+    ///
+    /// # Parameters
+    /// * address - Address to write. From 0x000 to size() - 1.
+    /// * value - Byte to write.
+    fn write(&mut self, address: u16, value: u8);
+
+    /// This function returns the size of the addressable memory. By default MEMORY_SIZE. Addresses
+    /// are 16 bits wide, so the VM never accesses more than 0x10000 bytes.
+    fn size(&self) -> usize {
+        MEMORY_SIZE
+    }
+}
@@ -0,0 +1,535 @@
+use core::fmt;
+
+use crate::specs::*;
+
+/// Behaviours that differ between interpreters and that some instructions rely on.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Quirk {
+    /// 8XY1, 8XY2 and 8XY3 reset VF on the COSMAC VIP.
+    VfReset,
+
+    /// 8XY6 and 8XYE with X different from Y shift VY on the COSMAC VIP and VX on SUPER-CHIP.
+    Shift,
+
+    /// FX55 and FX65 increment I on the COSMAC VIP but not on SUPER-CHIP.
+    LoadStore,
+
+    /// BNNN adds V0 to the address on the COSMAC VIP and VX on SUPER-CHIP.
+    Jump,
+
+    /// FX30 needs a big font set, only present on SUPER-CHIP like interpreters.
+    BigFont,
+}
+
+/// Problems found by lint in a program.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Issue {
+    /// Bytes from `start` to `end`, not included, that are neither executed nor read as sprites
+    /// or data by any reachable instruction.
+    Unreachable { start: usize, end: usize },
+
+    /// Jump or call at `address` to the second byte of another reachable instruction.
+    MisalignedJump { address: usize, target: usize },
+
+    /// Jump, call, skip or next instruction at `address` going to `target`, outside the
+    /// PROG_START..PROG_END program area.
+    OutOfRange { address: usize, target: usize },
+
+    /// Store at `address` writing the `target` byte of a reachable instruction.
+    SelfModifying { address: usize, target: usize },
+
+    /// Call at `address` that can be reached with a full stack.
+    StackOverflow(usize),
+
+    /// Return at `address` that can be reached with an empty stack.
+    StackUnderflow(usize),
+
+    /// Instruction at `address` whose result depends on the interpreter.
+    QuirkSpecific { address: usize, opcode: u16, quirk: Quirk },
+
+    /// Reachable instruction at `address` that is not defined by the Chip8 VM.
+    Undefined { address: usize, opcode: u16 },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Issue::Unreachable { start, end } => write!(f, "{:03X}..{:03X}: unreachable bytes", start, end),
+            Issue::MisalignedJump { address, target } => {
+                write!(f, "{:03X}: jump to {:03X}, in the middle of an instruction", address, target)
+            }
+            Issue::OutOfRange { address, target } => {
+                write!(f, "{:03X}: execution continues at {:03X}, outside the program", address, target)
+            }
+            Issue::SelfModifying { address, target } => {
+                write!(f, "{:03X}: store into the instruction byte at {:03X}", address, target)
+            }
+            Issue::StackOverflow(address) => write!(f, "{:03X}: call with a full stack", address),
+            Issue::StackUnderflow(address) => write!(f, "{:03X}: return with an empty stack", address),
+            Issue::QuirkSpecific { address, opcode, quirk } => {
+                write!(f, "{:03X}: {:04X} depends on the {:?} quirk", address, opcode, quirk)
+            }
+            Issue::Undefined { address, opcode } => write!(f, "{:03X}: undefined instruction {:04X}", address, opcode),
+        }
+    }
+}
+
+/// Value of the I register known by the analysis.
+#[derive(Copy, Clone, PartialEq)]
+enum Index {
+    Known(u16),
+    Unknown,
+}
+
+/// Analysis state of every program byte, as the start of an instruction.
+#[derive(Copy, Clone)]
+struct Cell {
+    /// True if an instruction starts at this byte in any execution path.
+    reached: bool,
+
+    /// Minimum and maximum number of return addresses in the stack.
+    min_depth: u8,
+    max_depth: u8,
+
+    /// Value of I before the instruction.
+    index: Index,
+
+    /// True if the byte is part of a reachable instruction.
+    code: bool,
+
+    /// True if the byte is read by a reachable instruction with a known I.
+    data: bool,
+}
+
+const UNREACHED: Cell = Cell {
+    reached: false,
+    min_depth: 0,
+    max_depth: 0,
+    index: Index::Unknown,
+    code: false,
+    data: false,
+};
+
+/// Static analysis of a program, walking its control flow from PROG_START.
+struct Lint<'a> {
+    rom: &'a [u8],
+    cells: [Cell; PROG_END - PROG_START],
+}
+
+/// Analyzes the program and reports its problems in address order. The analysis follows all
+/// the instructions reachable from PROG_START assuming that every call returns, and tracks
+/// the value of I set by ANNN to find the sprites, data and stores of the program.
+///
+/// Code only reached through BNNN is followed for jump tables of 1NNN instructions at the
+/// target address, so other computed jumps may report reachable code as unreachable.
+///
+/// # Parameters
+/// * rom - Program loaded at PROG_START.
+/// * report - Function receiving every issue found.
+///
+/// # Return
+/// * false - If the program does not fit in the program area and it is not analyzed.
+pub fn lint(rom: &[u8], report: &mut impl FnMut(Issue)) -> bool {
+    if rom.len() > PROG_END - PROG_START {
+        return false;
+    }
+
+    let mut lint = Lint {
+        rom,
+        cells: [UNREACHED; PROG_END - PROG_START],
+    };
+    lint.cells[0] = Cell {
+        reached: true,
+        index: Index::Known(0),
+        ..UNREACHED
+    };
+    lint.walk();
+    lint.mark();
+    lint.report(report);
+    true
+}
+
+/// Successors of an instruction, with the value of I after it.
+struct Successors {
+    targets: [usize; 2],
+    count: usize,
+    call: bool,
+    index: Index,
+}
+
+impl Successors {
+    fn add(&mut self, target: usize) {
+        self.targets[self.count] = target;
+        self.count += 1;
+    }
+}
+
+impl<'a> Lint<'a> {
+    /// Returns the opcode at the provided program address. Bytes after the ROM are 0.
+    fn opcode(&self, address: usize) -> u16 {
+        let byte = |address: usize| self.rom.get(address - PROG_START).copied().unwrap_or(0) as u16;
+        byte(address) << 8 | byte(address + 1)
+    }
+
+    /// Returns true if a whole instruction at the address is inside the program area.
+    fn in_program(address: usize) -> bool {
+        address >= PROG_START && address + 2 <= PROG_END
+    }
+
+    fn cell(&self, address: usize) -> Option<&Cell> {
+        if address >= PROG_START {
+            self.cells.get(address - PROG_START)
+        } else {
+            None
+        }
+    }
+
+    fn is_instruction(&self, address: usize) -> bool {
+        self.cell(address).is_some_and(|cell| cell.reached)
+    }
+
+    /// Returns the instructions executed after the one at the address, ignoring returns.
+    fn successors(&self, address: usize, index: Index) -> Successors {
+        let opcode = self.opcode(address);
+        let nnn = (opcode & 0x0FFF) as usize;
+        let next = address + 2;
+        let mut successors = Successors {
+            targets: [0; 2],
+            count: 0,
+            call: false,
+            index,
+        };
+        if !Lint::is_defined(opcode) {
+            return successors;
+        }
+        match opcode {
+            0x00E0 => successors.add(next),
+            0x1000..=0x1FFF => successors.add(nnn),
+            0x2000..=0x2FFF => {
+                successors.add(nnn);
+                successors.call = true;
+            }
+            0x3000..=0x5FFF | 0x9000..=0x9FFF | 0xE000..=0xEFFF => {
+                successors.add(next);
+                successors.add(next + 2);
+            }
+            0xA000..=0xAFFF => {
+                successors.add(next);
+                successors.index = Index::Known(nnn as u16);
+            }
+            0xF000..=0xFFFF if matches!(opcode & 0xFF, 0x1E | 0x29 | 0x30 | 0x55 | 0x65) => {
+                successors.add(next);
+                successors.index = Index::Unknown;
+            }
+            0x00EE | 0xB000..=0xBFFF => {}
+            _ => successors.add(next),
+        }
+        successors
+    }
+
+    /// Returns true if the instruction is defined by the Chip8 VM.
+    fn is_defined(opcode: u16) -> bool {
+        match opcode {
+            0x00E0 | 0x00EE | 0x1000..=0x4FFF | 0x6000..=0x7FFF | 0xA000..=0xDFFF => true,
+            0x5000..=0x5FFF | 0x9000..=0x9FFF => opcode & 0xF == 0,
+            0x8000..=0x8FFF => matches!(opcode & 0xF, 0x0..=0x7 | 0xE),
+            0xE000..=0xEFFF => matches!(opcode & 0xFF, 0x9E | 0xA1),
+            0xF000..=0xFFFF => matches!(opcode & 0xFF, 0x07 | 0x0A | 0x15 | 0x18 | 0x1E | 0x29 | 0x30 | 0x33 | 0x55 | 0x65),
+            _ => false,
+        }
+    }
+
+    /// Returns the quirk the instruction depends on, if any.
+    fn quirk(opcode: u16) -> Option<Quirk> {
+        let x = opcode >> 8 & 0xF;
+        let y = opcode >> 4 & 0xF;
+        match opcode & 0xF00F {
+            0x8001..=0x8003 => Some(Quirk::VfReset),
+            0x8006 | 0x800E if x != y => Some(Quirk::Shift),
+            0xB000..=0xBFFF => Some(Quirk::Jump),
+            _ => match opcode & 0xF0FF {
+                0xF055 | 0xF065 => Some(Quirk::LoadStore),
+                0xF030 => Some(Quirk::BigFont),
+                _ => None,
+            },
+        }
+    }
+
+    /// Merges the state of an execution path into the instruction at the address.
+    ///
+    /// # Return
+    /// * true - If the state of the instruction changed.
+    fn join(&mut self, address: usize, min_depth: u8, max_depth: u8, index: Index) -> bool {
+        let cell = &mut self.cells[address - PROG_START];
+        let joined = if cell.reached {
+            Cell {
+                min_depth: cell.min_depth.min(min_depth),
+                max_depth: cell.max_depth.max(max_depth),
+                index: if cell.index == index { index } else { Index::Unknown },
+                ..*cell
+            }
+        } else {
+            Cell {
+                reached: true,
+                min_depth,
+                max_depth,
+                index,
+                ..*cell
+            }
+        };
+        let changed = !cell.reached || joined.min_depth != cell.min_depth || joined.max_depth != cell.max_depth || joined.index != cell.index;
+        *cell = joined;
+        changed
+    }
+
+    /// Propagates the state of the reachable instructions until it does not change. Depths are
+    /// limited to STACK_SIZE, so the states only grow a bounded number of times.
+    fn walk(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for offset in 0..self.cells.len() {
+                let cell = self.cells[offset];
+                if !cell.reached {
+                    continue;
+                }
+
+                let address = PROG_START + offset;
+                let opcode = self.opcode(address);
+                let successors = self.successors(address, cell.index);
+                for target in successors.targets[..successors.count].iter() {
+                    if !Lint::in_program(*target) {
+                        continue;
+                    }
+                    let (min_depth, max_depth) = if successors.call {
+                        let limit = STACK_SIZE as u8;
+                        ((cell.min_depth + 1).min(limit), (cell.max_depth + 1).min(limit))
+                    } else {
+                        (cell.min_depth, cell.max_depth)
+                    };
+                    changed |= self.join(*target, min_depth, max_depth, successors.index);
+                }
+
+                // Calls continue after the subroutine returns, with any value of I.
+                if opcode & 0xF000 == 0x2000 && Lint::in_program(address + 2) {
+                    changed |= self.join(address + 2, cell.min_depth, cell.max_depth, Index::Unknown);
+                }
+
+                // Jump tables: BNNN followed by consecutive 1NNN instructions at NNN.
+                if opcode & 0xF000 == 0xB000 {
+                    let mut entry = (opcode & 0x0FFF) as usize;
+                    while Lint::in_program(entry) && self.opcode(entry) & 0xF000 == 0x1000 {
+                        changed |= self.join(entry, cell.min_depth, cell.max_depth, Index::Unknown);
+                        entry += 2;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks the bytes of the reachable instructions and the bytes they read as data.
+    fn mark(&mut self) {
+        for offset in 0..self.cells.len() {
+            let cell = self.cells[offset];
+            if !cell.reached {
+                continue;
+            }
+
+            let address = PROG_START + offset;
+            self.set_code(address);
+            self.set_code(address + 1);
+
+            if let Some((start, length)) = self.accessed(address, cell.index) {
+                for data in start..start + length {
+                    if (PROG_START..PROG_END).contains(&data) {
+                        self.cells[data - PROG_START].data = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_code(&mut self, address: usize) {
+        if let Some(cell) = self.cells.get_mut(address - PROG_START) {
+            cell.code = true;
+        }
+    }
+
+    /// Returns the first address and the number of bytes read or written by the instruction
+    /// through I, if I is known.
+    fn accessed(&self, address: usize, index: Index) -> Option<(usize, usize)> {
+        let i = match index {
+            Index::Known(i) => i as usize,
+            Index::Unknown => return None,
+        };
+        let opcode = self.opcode(address);
+        let x = (opcode >> 8 & 0xF) as usize;
+        match opcode & 0xF0FF {
+            0xF033 => Some((i, 3)),
+            0xF055 | 0xF065 => Some((i, x + 1)),
+            _ if opcode & 0xF000 == 0xD000 => Some((i, (opcode & 0xF) as usize)),
+            _ => None,
+        }
+    }
+
+    /// Reports the issues of every instruction and the unreachable bytes, in address order.
+    fn report(&self, report: &mut impl FnMut(Issue)) {
+        let mut unreachable = None;
+        for offset in 0..self.rom.len() {
+            let address = PROG_START + offset;
+            let cell = self.cells[offset];
+            if cell.code || cell.data {
+                if let Some(start) = unreachable.take() {
+                    report(Issue::Unreachable { start, end: address });
+                }
+            } else if unreachable.is_none() {
+                unreachable = Some(address);
+            }
+
+            if cell.reached {
+                self.report_instruction(address, &cell, report);
+            }
+        }
+        if let Some(start) = unreachable {
+            report(Issue::Unreachable {
+                start,
+                end: PROG_START + self.rom.len(),
+            });
+        }
+
+        // Instructions executed after the end of the ROM, filled with zeros.
+        for offset in self.rom.len()..self.cells.len() {
+            let cell = self.cells[offset];
+            if cell.reached {
+                self.report_instruction(PROG_START + offset, &cell, report);
+            }
+        }
+    }
+
+    fn report_instruction(&self, address: usize, cell: &Cell, report: &mut impl FnMut(Issue)) {
+        let opcode = self.opcode(address);
+        if !Lint::is_defined(opcode) {
+            report(Issue::Undefined { address, opcode });
+            return;
+        }
+        if let Some(quirk) = Lint::quirk(opcode) {
+            report(Issue::QuirkSpecific { address, opcode, quirk });
+        }
+
+        let successors = self.successors(address, cell.index);
+        let jump = matches!(opcode & 0xF000, 0x1000 | 0x2000);
+        for target in successors.targets[..successors.count].iter() {
+            if !Lint::in_program(*target) {
+                report(Issue::OutOfRange { address, target: *target });
+            } else if jump && self.is_instruction(*target - 1) {
+                report(Issue::MisalignedJump { address, target: *target });
+            }
+        }
+
+        match opcode & 0xF000 {
+            0x2000 if cell.max_depth as usize == STACK_SIZE => report(Issue::StackOverflow(address)),
+            0x0000 if opcode == 0x00EE && cell.min_depth == 0 => report(Issue::StackUnderflow(address)),
+            _ => {}
+        }
+
+        if opcode & 0xF0FF == 0xF033 || opcode & 0xF0FF == 0xF055 {
+            if let Some((start, length)) = self.accessed(address, cell.index) {
+                let code = (start..start + length).find(|target| self.cell(*target).is_some_and(|cell| cell.code));
+                if let Some(target) = code {
+                    report(Issue::SelfModifying { address, target });
+                }
+            }
+        }
+    }
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn issues(opcodes: &[u16]) -> Vec<Issue> {
+        let rom: Vec<u8> = opcodes.iter().flat_map(|opcode| opcode.to_be_bytes()).collect();
+        let mut issues = Vec::new();
+        assert!(lint(&rom, &mut |issue| issues.push(issue)));
+        issues
+    }
+
+    #[test]
+    fn clean_program() {
+        // Draws the sprite after the loop, so it is not unreachable.
+        assert_eq!(issues(&[0x00E0, 0xA208, 0xD016, 0x1206, 0xF090, 0x9090, 0x90F0]), []);
+    }
+
+    #[test]
+    fn unreachable_bytes() {
+        assert_eq!(issues(&[0x1206, 0x1234, 0x5678, 0x1206]), [Issue::Unreachable { start: 0x202, end: 0x206 }]);
+    }
+
+    #[test]
+    fn misaligned_jump() {
+        // The jump executes the 0x0012 undefined instruction.
+        assert!(issues(&[0x6000, 0x1201]).contains(&Issue::MisalignedJump { address: 0x202, target: 0x201 }));
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(issues(&[0x1100]), [Issue::OutOfRange { address: 0x200, target: 0x100 }]);
+        assert_eq!(issues(&[0x2F00, 0x1202]), [Issue::OutOfRange { address: 0x200, target: 0xF00 }]);
+    }
+
+    #[test]
+    fn self_modifying() {
+        assert_eq!(
+            issues(&[0xA206, 0x6012, 0xF055, 0x6000, 0x1208]),
+            [
+                Issue::QuirkSpecific { address: 0x204, opcode: 0xF055, quirk: Quirk::LoadStore },
+                Issue::SelfModifying { address: 0x204, target: 0x206 },
+            ]
+        );
+    }
+
+    #[test]
+    fn stack_depth() {
+        assert_eq!(issues(&[0x00EE]), [Issue::StackUnderflow(0x200)]);
+        assert_eq!(issues(&[0x2200, 0x1202]), [Issue::StackOverflow(0x200)]);
+        assert_eq!(issues(&[0x2204, 0x1202, 0x00EE]), []);
+    }
+
+    #[test]
+    fn quirk_specific() {
+        assert_eq!(
+            issues(&[0x8011, 0x8006, 0x8126, 0xF030, 0x1208]),
+            [
+                Issue::QuirkSpecific { address: 0x200, opcode: 0x8011, quirk: Quirk::VfReset },
+                Issue::QuirkSpecific { address: 0x204, opcode: 0x8126, quirk: Quirk::Shift },
+                Issue::QuirkSpecific { address: 0x206, opcode: 0xF030, quirk: Quirk::BigFont },
+            ]
+        );
+    }
+
+    #[test]
+    fn undefined_instructions() {
+        assert_eq!(issues(&[0x8008]), [Issue::Undefined { address: 0x200, opcode: 0x8008 }]);
+        // Execution continues after the end of the ROM, filled with zeros.
+        assert_eq!(issues(&[0x6000]), [Issue::Undefined { address: 0x202, opcode: 0x0000 }]);
+    }
+
+    #[test]
+    fn jump_tables() {
+        assert_eq!(
+            issues(&[0xB202, 0x1206, 0x1206, 0x1206]),
+            [Issue::QuirkSpecific { address: 0x200, opcode: 0xB202, quirk: Quirk::Jump }]
+        );
+    }
+
+    #[test]
+    fn program_too_large() {
+        assert!(!lint(&[0; PROG_END - PROG_START + 1], &mut |_| panic!()));
+    }
+}
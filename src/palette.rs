@@ -0,0 +1,194 @@
+use crate::filter::FULL_INTENSITY;
+use crate::framebuffer::FrameBuffer;
+use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Constant defining the number of colors of a palette, one per combination of two planes.
+pub const PALETTE_SIZE: usize = 4;
+
+/// Constant defining the size in bytes of a RGBA image of the Chip8 VM screen.
+pub const RGBA_SIZE: usize = SCREEN_WIDTH * SCREEN_HEIGHT * 4;
+
+/// RGB color.
+pub type Rgb = [u8; 3];
+
+/// Palette mapping plane combinations to RGB colors.
+///
+/// The index of every color is the combination of the planes lit for a pixel, being bit 0 the
+/// first plane and bit 1 the second plane, following the XO-CHIP convention:
+/// * 0 - Background. No plane is lit.
+/// * 1 - Only the first plane is lit. This is the foreground color of monochrome programs.
+/// * 2 - Only the second plane is lit.
+/// * 3 - Both planes are lit.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Palette {
+    /// Colors indexed by plane combination.
+    pub colors: [Rgb; PALETTE_SIZE],
+}
+
+impl Palette {
+    /// White on black.
+    pub const MONOCHROME: Palette = Palette::new([
+        [0x00, 0x00, 0x00],
+        [0xFF, 0xFF, 0xFF],
+        [0xAA, 0xAA, 0xAA],
+        [0x55, 0x55, 0x55],
+    ]);
+
+    /// Green phosphor monitor.
+    pub const CLASSIC_GREEN: Palette = Palette::new([
+        [0x00, 0x11, 0x00],
+        [0x33, 0xFF, 0x33],
+        [0x11, 0x88, 0x11],
+        [0xAA, 0xFF, 0xAA],
+    ]);
+
+    /// Amber phosphor monitor.
+    pub const AMBER: Palette = Palette::new([
+        [0x11, 0x08, 0x00],
+        [0xFF, 0xB0, 0x00],
+        [0x88, 0x58, 0x00],
+        [0xFF, 0xDD, 0x77],
+    ]);
+
+    /// Reflective LCD, as found in handheld devices.
+    pub const LCD: Palette = Palette::new([
+        [0x9B, 0xBC, 0x0F],
+        [0x0F, 0x38, 0x0F],
+        [0x30, 0x62, 0x30],
+        [0x8B, 0xAC, 0x0F],
+    ]);
+
+    /// Default colors of the Octo development environment.
+    pub const OCTO: Palette = Palette::new([
+        [0x99, 0x66, 0x00],
+        [0xFF, 0xCC, 0x00],
+        [0xFF, 0x66, 0x00],
+        [0x66, 0x22, 0x00],
+    ]);
+
+    /// Creates a new palette with the provided colors.
+    ///
+    /// # Parameters
+    /// * colors - Colors indexed by plane combination.
+    pub const fn new(colors: [Rgb; PALETTE_SIZE]) -> Self {
+        Palette { colors }
+    }
+
+    /// Returns the color for the provided plane combination.
+    ///
+    /// # Parameters
+    /// * planes - Plane combination. Only the two least significant bits are used.
+    pub fn color(&self, planes: u8) -> Rgb {
+        self.colors[(planes & 0x3) as usize]
+    }
+
+    /// Converts a monochrome framebuffer to RGBA, using the background and first plane colors.
+    ///
+    /// # Parameters
+    /// * framebuffer - Framebuffer to convert.
+    /// * rgba - Destination buffer of RGBA_SIZE bytes.
+    pub fn to_rgba(&self, framebuffer: &FrameBuffer, rgba: &mut [u8]) {
+        self.planes_to_rgba(framebuffer, &FrameBuffer::default(), rgba);
+    }
+
+    /// Converts two framebuffer planes to RGBA.
+    ///
+    /// # Parameters
+    /// * first - First plane.
+    /// * second - Second plane.
+    /// * rgba - Destination buffer of RGBA_SIZE bytes.
+    pub fn planes_to_rgba(&self, first: &FrameBuffer, second: &FrameBuffer, rgba: &mut [u8]) {
+        for (position, pixel) in rgba.chunks_exact_mut(4).take(SCREEN_WIDTH * SCREEN_HEIGHT).enumerate() {
+            let (x, y) = (position % SCREEN_WIDTH, position / SCREEN_WIDTH);
+            let planes = first.is_on(x, y) as u8 | (second.is_on(x, y) as u8) << 1;
+            Self::write(pixel, self.color(planes));
+        }
+    }
+
+    /// Converts pixel intensities, as produced by the PersistenceFilter, to RGBA. Every pixel
+    /// is blended between the background and first plane colors.
+    ///
+    /// # Parameters
+    /// * intensities - Intensity of every pixel, row by row.
+    /// * rgba - Destination buffer of RGBA_SIZE bytes.
+    pub fn intensities_to_rgba(&self, intensities: &[u8], rgba: &mut [u8]) {
+        let background = self.colors[0];
+        let foreground = self.colors[1];
+        for (pixel, intensity) in rgba.chunks_exact_mut(4).zip(intensities.iter()) {
+            let mut color = [0; 3];
+            for (channel, value) in color.iter_mut().enumerate() {
+                *value = blend(background[channel], foreground[channel], *intensity);
+            }
+            Self::write(pixel, color);
+        }
+    }
+
+    fn write(pixel: &mut [u8], color: Rgb) {
+        pixel[..3].copy_from_slice(&color);
+        pixel[3] = 0xFF;
+    }
+}
+
+/// Default implementation for Palette structure.
+///
+/// Initializes a monochrome white on black palette.
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::MONOCHROME
+    }
+}
+
+fn blend(background: u8, foreground: u8, intensity: u8) -> u8 {
+    let intensity = intensity as u16;
+    let full = FULL_INTENSITY as u16;
+    ((background as u16 * (full - intensity) + foreground as u16 * intensity) / full) as u8
+}
+
+//
+// Tests
+//
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_rgba() {
+        let mut framebuffer = FrameBuffer::default();
+        framebuffer.set(1, 0, true);
+        let mut rgba = [0; RGBA_SIZE];
+
+        Palette::OCTO.to_rgba(&framebuffer, &mut rgba);
+
+        assert_eq!(rgba[0..4], [0x99, 0x66, 0x00, 0xFF]);
+        assert_eq!(rgba[4..8], [0xFF, 0xCC, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn planes_to_rgba() {
+        let mut first = FrameBuffer::default();
+        let mut second = FrameBuffer::default();
+        first.set(0, 0, true);
+        second.set(0, 0, true);
+        second.set(1, 0, true);
+        let mut rgba = [0; RGBA_SIZE];
+
+        Palette::OCTO.planes_to_rgba(&first, &second, &mut rgba);
+
+        assert_eq!(rgba[0..4], [0x66, 0x22, 0x00, 0xFF]);
+        assert_eq!(rgba[4..8], [0xFF, 0x66, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn intensities_to_rgba() {
+        let mut intensities = [0; SCREEN_WIDTH * SCREEN_HEIGHT];
+        intensities[0] = FULL_INTENSITY;
+        intensities[1] = 51;
+        let mut rgba = [0; RGBA_SIZE];
+
+        Palette::MONOCHROME.intensities_to_rgba(&intensities, &mut rgba);
+
+        assert_eq!(rgba[0..4], [0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(rgba[4..8], [51, 51, 51, 0xFF]);
+        assert_eq!(rgba[8..12], [0x00, 0x00, 0x00, 0xFF]);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::font::Font;
+
+/// Constant defining the default address of the font. Many interpreters use 0x050 instead.
+pub const FONT_ADDRESS: u16 = 0x000;
+
+/// Policy applied to memory accesses and instruction fetches beyond the end of the memory.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MemoryPolicy {
+    /// Addresses wrap around the end of the memory, at 12 bits with 4 KiB of memory like the
+    /// COSMAC VIP.
+    Wrap,
+
+    /// Addresses are clamped to the last byte of the memory.
+    Clamp,
+
+    /// The access stops the execution with a chip::Fault::Address fault.
+    Fault,
+}
+
+/// Width of the I register and behaviour of FX1E when it exceeds 0xFFF.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum IndexRegister {
+    /// I is 12 bits wide and wraps around 0xFFF.
+    Wrap12,
+
+    /// I is 16 bits wide and FX1E sets VF to 1 when I exceeds 0xFFF, or to 0 otherwise, like
+    /// the Amiga interpreter. Spacefight 2091! relies on it.
+    Overflow,
+
+    /// I is 16 bits wide, like XO-CHIP, which addresses 64 KiB.
+    Bits16,
+}
+
+/// Behaviour of DXYN at the screen edges and value of the collision flag.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SpriteMode {
+    /// Every pixel wraps around the screen edges. VF is set to 1 if any pixel collides.
+    Wrap,
+
+    /// The start coordinates wrap around the screen, and the sprite is clipped at the right
+    /// and bottom edges, like the COSMAC VIP. VF is set to 1 if any pixel collides.
+    Clip,
+
+    /// Sprites are clipped like Clip, and VF is set to the number of rows with a collision or
+    /// clipped at the bottom edge, like the HP48 SCHIP.
+    SchipRows,
+}
+
+/// Quirks structure with the behaviours that differ between Chip8 interpreters.
+///
+/// The constants select the behaviour of well known interpreters, and fields can be changed
+/// from them for custom configurations:
+///
+/// ```
+/// use chip8vm::font::Font;
+/// use chip8vm::quirks::Quirks;
+///
+/// let quirks = Quirks {
+///     font_address: 0x050,
+///     ..Quirks::VIP
+/// };
+/// assert_eq!(quirks.font.set, Font::VIP.set);
+/// ```
+#[derive(Copy, Clone)]
+pub struct Quirks {
+    /// Font loaded in the interpreter memory.
+    pub font: &'static Font,
+
+    /// Address where the font is loaded. Addresses overlapping the program are clamped by the
+    /// Chip, so the font ends at the program start address.
+    pub font_address: u16,
+
+    /// Policy for addresses beyond the end of the memory.
+    pub memory: MemoryPolicy,
+
+    /// Width and FX1E overflow behaviour of the I register.
+    pub index: IndexRegister,
+
+    /// Behaviour of sprites at the screen edges and collision flag.
+    pub sprite: SpriteMode,
+
+    /// If true, DXYN draws at most once per 60Hz frame, waiting for the next frame like the
+    /// COSMAC VIP waits for the display interrupt. Frames are delimited by Chip::tick_timers.
+    pub display_wait: bool,
+
+    /// If true, 8XY1, 8XY2 and 8XY3 reset VF to 0, like the COSMAC VIP.
+    pub vf_reset: bool,
+
+    /// If true, 8XY6 and 8XYE shift VY and store the result in VX, like the COSMAC VIP.
+    /// Otherwise VX is shifted in place, like the HP48 SCHIP.
+    pub shift_vy: bool,
+
+    /// If true, FX55 and FX65 leave I pointing after the last register, like the COSMAC VIP.
+    /// Otherwise I is not changed, like the HP48 SCHIP.
+    pub load_store_increment: bool,
+}
+
+impl Quirks {
+    /// Behaviour of the Chip8 VM since its first version.
+    pub const CHIP8: Quirks = Quirks {
+        font: &Font::CHIP8,
+        font_address: FONT_ADDRESS,
+        memory: MemoryPolicy::Wrap,
+        index: IndexRegister::Bits16,
+        sprite: SpriteMode::Wrap,
+        display_wait: false,
+        vf_reset: false,
+        shift_vy: false,
+        load_store_increment: true,
+    };
+
+    /// Behaviour of the COSMAC VIP interpreter.
+    pub const VIP: Quirks = Quirks {
+        font: &Font::VIP,
+        sprite: SpriteMode::Clip,
+        display_wait: true,
+        vf_reset: true,
+        shift_vy: true,
+        ..Quirks::CHIP8
+    };
+
+    /// Behaviour of the DREAM 6800 interpreter.
+    pub const DREAM6800: Quirks = Quirks {
+        font: &Font::DREAM6800,
+        ..Quirks::CHIP8
+    };
+
+    /// Behaviour of the ETI-660 interpreter.
+    pub const ETI660: Quirks = Quirks {
+        font: &Font::ETI660,
+        ..Quirks::CHIP8
+    };
+
+    /// Behaviour of the HP48 SCHIP interpreter.
+    pub const SCHIP: Quirks = Quirks {
+        font: &Font::SCHIP,
+        sprite: SpriteMode::SchipRows,
+        load_store_increment: false,
+        ..Quirks::CHIP8
+    };
+}
+
+/// Default implementation for Quirks structure.
+///
+/// Returns the CHIP8 quirks.
+impl Default for Quirks {
+    fn default() -> Self {
+        Quirks::CHIP8
+    }
+}
@@ -7,6 +7,7 @@ pub const FLAG: usize = 0xF;
 // 0x200..0xE8F -> Program
 // 0xE90..0xFFF -> Reserved for variables and display
 pub const MEM_SIZE: usize = 4096;
+pub const ADDRESS_SPACE: usize = 0x10000;
 pub const PROG_START: usize = 0x200;
 pub const PROG_END: usize = 0xE8F;
 
@@ -0,0 +1,188 @@
+//! Conformance tests running test ROMs headlessly for every quirks profile, with a scripted
+//! keypad, and comparing the final screen with the checked-in golden images in
+//! `tests/golden/<profile>/<rom>.pbm` through `capture::write_pbm`.
+//!
+//! Golden images are never written by the tests. The images of the ROMs of the suite show a
+//! check mark for every check that passes, following the documented behaviour of the COSMAC VIP
+//! and SUPER-CHIP, and the images of the reference ROMs must be checked by hand against a
+//! reference interpreter.
+
+mod roms;
+
+use std::fs;
+use std::path::PathBuf;
+
+use chip8vm::capture::write_pbm;
+use chip8vm::chip::Chip;
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::quirks::Quirks;
+use chip8vm::{Keypad, Random, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+use roms::*;
+
+/// Number of instructions executed per frame.
+const TICKS_X_FRAME: u32 = 10;
+
+/// Frames executed by the check based ROMs. With the display wait quirk every cell takes a
+/// frame.
+const CHECK_FRAMES: u32 = 120;
+
+/// Frames a key is pressed, and released, by the keypad script.
+const PRESS_FRAMES: u32 = 5;
+
+const PROFILES: [(&str, Quirks); 5] = [
+    ("chip8", Quirks::CHIP8),
+    ("vip", Quirks::VIP),
+    ("dream6800", Quirks::DREAM6800),
+    ("eti660", Quirks::ETI660),
+    ("schip", Quirks::SCHIP),
+];
+
+struct TestRandom;
+
+impl Random for TestRandom {
+    fn range(&mut self) -> u8 {
+        0xA5
+    }
+}
+
+/// Keypad pressing the keys of a script, one at a time.
+struct ScriptedKeypad {
+    key: Option<u8>,
+}
+
+impl Keypad for ScriptedKeypad {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.key == Some(keycode)
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        self.key
+    }
+}
+
+/// Runs the ROM for the number of frames, pressing every key of the script during PRESS_FRAMES
+/// frames followed by PRESS_FRAMES frames without keys.
+fn run(rom: &[u8], quirks: Quirks, frames: u32, script: &[u8]) -> FrameBuffer {
+    let mut chip = Chip::default();
+    let mut random = TestRandom;
+    let mut screen = FrameBuffer::default();
+    let mut keypad = ScriptedKeypad { key: None };
+
+    chip.set_quirks(quirks);
+    assert!(chip.load_rom(rom));
+
+    for frame in 0..frames {
+        let step = (frame / PRESS_FRAMES) as usize;
+        keypad.key = if step % 2 == 0 { script.get(step / 2).copied() } else { None };
+
+        for _tick in 0..TICKS_X_FRAME {
+            assert!(chip.tick(&mut random, &mut screen, &keypad), "Invalid instruction.");
+        }
+        chip.tick_timers();
+    }
+
+    screen
+}
+
+/// Reference test ROMs, not bundled with the suite, with the frames they run and their keypad
+/// script per profile. The scripts select the CHIP-8 or SUPER-CHIP tests in the menus of the
+/// ROMs and press the keys requested by the keypad test.
+const REFERENCE_ROMS: [(&str, u32, &[u8], &[u8]); 4] = [
+    ("3-corax+", 240, &[], &[]),
+    ("4-flags", 240, &[], &[]),
+    ("5-quirks", 1200, &[0x1], &[0x2]),
+    ("6-keypad", 240, &[0x3, 0x5], &[0x3, 0x5]),
+];
+
+/// Returns the path of a file of the tests folder.
+fn test_path(parts: &[&str]) -> PathBuf {
+    let mut path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests"].iter().collect();
+    path.extend(parts);
+    path
+}
+
+/// Returns a PBM image as text, to show the differences with the golden images.
+fn to_text(image: &[u8]) -> String {
+    let pixels = &image[image.len().saturating_sub(SCREEN_WIDTH * SCREEN_HEIGHT / 8)..];
+    let mut text = String::new();
+    for y in 0..SCREEN_HEIGHT {
+        for x in 0..SCREEN_WIDTH {
+            let on = pixels.get((y * SCREEN_WIDTH + x) / 8).is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0);
+            text.push(if on { '#' } else { '.' });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Compares the screen with the golden image of the ROM for the profile.
+fn assert_golden(profile: &str, name: &str, framebuffer: &FrameBuffer) {
+    let mut image = Vec::new();
+    write_pbm(framebuffer, &mut |data| image.extend_from_slice(data));
+
+    let path = test_path(&["golden", profile, &format!("{}.pbm", name)]);
+    let golden = fs::read(&path).unwrap_or_else(|_| panic!("Unable to read {}.", path.display()));
+    assert!(
+        image == golden,
+        "Screen of {} with the {} profile:\n{}\nGolden image {}:\n{}",
+        name,
+        profile,
+        to_text(&image),
+        path.display(),
+        to_text(&golden)
+    );
+}
+
+#[test]
+fn opcode_test() {
+    for (profile, quirks) in PROFILES.iter() {
+        assert_golden(profile, "opcode", &run(&opcode(), *quirks, CHECK_FRAMES, &[]));
+    }
+}
+
+#[test]
+fn flags_test() {
+    for (profile, quirks) in PROFILES.iter() {
+        assert_golden(profile, "flags", &run(&flags(), *quirks, CHECK_FRAMES, &[]));
+    }
+}
+
+#[test]
+fn quirks_test() {
+    for (profile, quirks) in PROFILES.iter() {
+        assert_golden(profile, "quirks", &run(&roms::quirks(), *quirks, CHECK_FRAMES, &[]));
+    }
+}
+
+#[test]
+fn keypad_test() {
+    let frames = KEYPAD_KEYS.len() as u32 * PRESS_FRAMES * 2;
+    for (profile, quirks) in PROFILES.iter() {
+        assert_golden(profile, "keypad", &run(&keypad(), *quirks, frames, &KEYPAD_KEYS));
+    }
+}
+
+/// Runs the reference ROMs copied to `tests/roms`, skipping the ROMs and profiles without a
+/// golden image.
+#[test]
+fn reference_roms() {
+    for (name, frames, chip8_script, schip_script) in REFERENCE_ROMS.iter() {
+        let rom = match fs::read(test_path(&["roms", &format!("{}.ch8", name)])) {
+            Ok(rom) => rom,
+            Err(_) => {
+                eprintln!("Skipping {}: tests/roms/{}.ch8 not found.", name, name);
+                continue;
+            }
+        };
+
+        for (profile, quirks) in PROFILES.iter() {
+            if !test_path(&["golden", profile, &format!("{}.pbm", name)]).exists() {
+                eprintln!("Skipping {} with the {} profile: no golden image.", name, profile);
+                continue;
+            }
+            let script = if *profile == "schip" { schip_script } else { chip8_script };
+            assert_golden(profile, name, &run(&rom, *quirks, *frames, script));
+        }
+    }
+}
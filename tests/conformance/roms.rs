@@ -0,0 +1,222 @@
+//! Test ROMs of the conformance suite, assembled with a minimal assembler.
+//!
+//! Check based ROMs draw a cell for every check, in rows of CELLS_PER_ROW cells of
+//! CELL_WIDTH x CELL_HEIGHT pixels: a check mark when the check passes and a cross when it
+//! fails. Registers VC and VD hold the position of the next cell.
+
+/// Address of the check mark sprite.
+const PASS: u16 = 0x202;
+/// Address of the cross sprite.
+const FAIL: u16 = 0x207;
+/// Address of the first instruction after the sprites.
+const CODE: u16 = 0x20C;
+
+/// Check mark sprite, 4x5 pixels.
+pub const PASS_SPRITE: [u8; 5] = [0x10, 0x10, 0xA0, 0xA0, 0x40];
+/// Cross sprite, 4x5 pixels.
+pub const FAIL_SPRITE: [u8; 5] = [0x90, 0x60, 0x60, 0x90, 0x00];
+
+pub const CELL_WIDTH: usize = 5;
+pub const CELL_HEIGHT: usize = 6;
+pub const CELLS_PER_ROW: usize = 12;
+
+/// Keys pressed by the keypad ROM test script, in order.
+pub const KEYPAD_KEYS: [u8; 8] = [0x1, 0x2, 0x3, 0xC, 0x4, 0xA, 0xF, 0x0];
+
+/// ROM under construction, loaded at 0x200.
+pub struct Rom {
+    data: Vec<u8>,
+    checks: usize,
+}
+
+impl Rom {
+    /// Creates a ROM that jumps over the sprites and clears the screen.
+    fn new() -> Rom {
+        let mut rom = Rom {
+            data: Vec::new(),
+            checks: 0,
+        };
+        rom.op(0x1000 | CODE);
+        rom.data.extend_from_slice(&PASS_SPRITE);
+        rom.data.extend_from_slice(&FAIL_SPRITE);
+        rom.op(0x00E0);
+        rom
+    }
+
+    /// Address of the next instruction.
+    fn here(&self) -> u16 {
+        0x200 + self.data.len() as u16
+    }
+
+    fn op(&mut self, opcode: u16) -> &mut Rom {
+        self.data.extend_from_slice(&opcode.to_be_bytes());
+        self
+    }
+
+    fn ops(&mut self, opcodes: &[u16]) -> &mut Rom {
+        for opcode in opcodes {
+            self.op(*opcode);
+        }
+        self
+    }
+
+    /// Draws the next cell, passing if the skip instruction skips. Drawing changes VF, so checks
+    /// of VF must be done first.
+    fn check(&mut self, skip_if_ok: u16) -> &mut Rom {
+        self.ops(&[0xA000 | PASS, skip_if_ok, 0xA000 | FAIL, 0xDCD5, 0x7C00 | CELL_WIDTH as u16]);
+        self.checks += 1;
+        if self.checks % CELLS_PER_ROW == 0 {
+            self.ops(&[0x6C00, 0x7D00 | CELL_HEIGHT as u16]);
+        }
+        self
+    }
+
+    /// Ends the program with an infinite loop.
+    fn end(&mut self) -> Vec<u8> {
+        let here = self.here();
+        self.op(0x1000 | here);
+        self.data.clone()
+    }
+}
+
+/// Number of checks of the opcode ROM.
+pub const OPCODE_CHECKS: usize = 33;
+
+/// Checks the result of every instruction, and draws the 16 font characters at the bottom.
+pub fn opcode() -> Vec<u8> {
+    let mut rom = Rom::new();
+
+    // 6XNN, 7XNN without carry flag
+    rom.op(0x6005).check(0x3005);
+    rom.ops(&[0x6005, 0x7003]).check(0x3008);
+    rom.ops(&[0x6F00, 0x60FF, 0x7002]).check(0x3F00).check(0x3001);
+    // 8XY0 to 8XY7, 8XYE
+    rom.ops(&[0x6107, 0x8010]).check(0x3007);
+    rom.ops(&[0x6003, 0x610C, 0x8011]).check(0x300F);
+    rom.ops(&[0x6006, 0x6103, 0x8012]).check(0x3002);
+    rom.ops(&[0x6006, 0x6103, 0x8013]).check(0x3005);
+    rom.ops(&[0x6014, 0x6122, 0x8014]).check(0x3036);
+    rom.ops(&[0x6030, 0x6110, 0x8015]).check(0x3020);
+    rom.ops(&[0x6008, 0x8006]).check(0x3004);
+    rom.ops(&[0x6010, 0x6130, 0x8017]).check(0x3020);
+    rom.ops(&[0x6011, 0x800E]).check(0x3022);
+    // 3XNN, 4XNN, 5XY0, 9XY0
+    rom.op(0x6003).check(0x3003);
+    rom.op(0x6003).check(0x4004);
+    rom.ops(&[0x6003, 0x6103]).check(0x5010);
+    rom.ops(&[0x6003, 0x6104]).check(0x9010);
+    // ANNN, FX1E, FX55, FX65, FX33
+    rom.ops(&[0xA000 | PASS, 0xF065]).check(0x3010);
+    rom.ops(&[0xA200, 0x6002, 0xF01E, 0xF065]).check(0x3010);
+    rom.ops(&[0xAE00, 0x6012, 0x6134, 0xF155, 0xAE00, 0x6000, 0x6100, 0xF165]);
+    rom.check(0x3012).check(0x3134);
+    rom.ops(&[0xAE00, 0x607B, 0xF033, 0xAE00, 0xF265]);
+    rom.check(0x3001).check(0x3102).check(0x3203);
+    // 1NNN, BNNN
+    rom.op(0x6E01);
+    let target = rom.here() + 4;
+    rom.ops(&[0x1000 | target, 0x6E00]).check(0x3E01);
+    rom.ops(&[0x6E01, 0x6002]);
+    let target = rom.here() + 2;
+    rom.ops(&[0xB000 | target, 0x6E00]).check(0x3E01);
+    // 2NNN, 00EE
+    let subroutine = rom.here() + 2;
+    rom.ops(&[0x1000 | (subroutine + 4), 0x6042, 0x00EE, 0x6000, 0x2000 | subroutine]);
+    rom.check(0x3042);
+    // CXNN
+    rom.op(0xC000).check(0x3000);
+    // FX15, FX07
+    rom.ops(&[0x6020, 0xF015, 0xF107]).check(0x4100);
+    // EX9E, EXA1 without pressed keys
+    rom.ops(&[0x6005, 0x6E00, 0xE09E, 0x6E01]).check(0x3E01);
+    rom.op(0x6005).check(0xE0A1);
+    // DXYN collision, with a single row so it does not depend on the sprite mode
+    rom.ops(&[0xA000 | PASS, 0x613C, 0x6213, 0xD121]).check(0x3F00);
+    rom.op(0xD121).check(0x3F01);
+    // FX29, drawing all the characters
+    rom.ops(&[0x6100, 0x621A]);
+    for character in 0..16 {
+        rom.ops(&[0x6000 | character, 0xF029, 0xD125, 0x7104]);
+    }
+
+    assert_eq!(rom.checks, OPCODE_CHECKS);
+    rom.end()
+}
+
+/// Number of checks of the flags ROM.
+pub const FLAGS_CHECKS: usize = 27;
+
+/// Checks the VF flag of the arithmetic instructions, also when VF is an operand.
+pub fn flags() -> Vec<u8> {
+    let mut rom = Rom::new();
+
+    // 8XY4 carry
+    rom.ops(&[0x6010, 0x6120, 0x8014]).check(0x3F00).check(0x3030);
+    rom.ops(&[0x60F0, 0x6120, 0x8014]).check(0x3F01).check(0x3010);
+    // 8XY5 borrow
+    rom.ops(&[0x6030, 0x6110, 0x8015]).check(0x3F01).check(0x3020);
+    rom.ops(&[0x6010, 0x6130, 0x8015]).check(0x3F00).check(0x30E0);
+    // 8XY7 borrow
+    rom.ops(&[0x6010, 0x6130, 0x8017]).check(0x3F01).check(0x3020);
+    rom.ops(&[0x6030, 0x6110, 0x8017]).check(0x3F00).check(0x30E0);
+    // 8XY6 and 8XYE shifted bit, with X equal to Y
+    rom.ops(&[0x6005, 0x8006]).check(0x3F01).check(0x3002);
+    rom.ops(&[0x6004, 0x8006]).check(0x3F00);
+    rom.ops(&[0x6081, 0x800E]).check(0x3F01).check(0x3002);
+    rom.ops(&[0x6001, 0x800E]).check(0x3F00);
+    // VF as X operand, the flag overwrites the result
+    rom.ops(&[0x6F10, 0x6120, 0x8F14]).check(0x3F00);
+    rom.ops(&[0x6FF0, 0x6120, 0x8F14]).check(0x3F01);
+    rom.ops(&[0x6F30, 0x6110, 0x8F15]).check(0x3F01);
+    rom.ops(&[0x6F04, 0x8FF6]).check(0x3F00);
+    rom.ops(&[0x6F05, 0x8FF6]).check(0x3F01);
+    rom.ops(&[0x6F01, 0x8FFE]).check(0x3F00);
+    rom.ops(&[0x6F81, 0x8FFE]).check(0x3F01);
+    // VF as Y operand
+    rom.ops(&[0x6F10, 0x6020, 0x80F4]).check(0x3030);
+    // 7XNN does not change VF
+    rom.ops(&[0x6F05, 0x60FF, 0x7002]).check(0x3F05);
+
+    assert_eq!(rom.checks, FLAGS_CHECKS);
+    rom.end()
+}
+
+/// Draws a check mark for every quirk present in the interpreter, and a cross for every quirk
+/// not present:
+/// 1. 8XY1 resets VF.
+/// 2. FX55 increments I.
+/// 3. 8XY6 shifts VY.
+/// 4. Sprites wrap around the screen edges.
+/// 5. FX1E sets VF when I overflows 0xFFF.
+/// 6. DXYN sets VF to the number of rows with collisions.
+/// 7. DXYN waits for the display, drawing once per frame.
+pub fn quirks() -> Vec<u8> {
+    let mut rom = Rom::new();
+
+    rom.ops(&[0x6F05, 0x6001, 0x6102, 0x8011]).check(0x3F00);
+    rom.ops(&[0xAE00, 0x6011, 0xF055, 0xF065]).check(0x3000);
+    rom.ops(&[0x6001, 0x6104, 0x8016]).check(0x3002);
+    // Pixels wrapped from x = 62 collide with the sprite at x = 0. The sprites are erased.
+    rom.ops(&[0xA000 | PASS, 0x613E, 0x621A, 0xD125, 0x6100, 0xD125, 0x8EF0, 0xD125, 0x613E, 0xD125]);
+    rom.check(0x3E01);
+    rom.ops(&[0xAFFF, 0x6001, 0x6F00, 0xF01E]).check(0x3F01);
+    // A 2 rows sprite drawn twice collides in both rows. The sprites are erased.
+    rom.ops(&[0xA000 | PASS, 0x6100, 0x621A, 0xD122, 0xD122]).check(0x3F02);
+    // 3 draws with the delay timer at 5 take more than a frame only waiting for the display.
+    // V2 is divided by 4, so it is 0 if the timer decreased at least twice.
+    rom.ops(&[0x6105, 0xF115, 0xD120, 0xD120, 0xD120, 0xF207, 0x8226, 0x8226]).check(0x3200);
+
+    rom.end()
+}
+
+/// Waits for a key, draws its character and waits for its release, forever.
+pub fn keypad() -> Vec<u8> {
+    let mut rom = Rom::new();
+
+    let wait_key = rom.here();
+    rom.ops(&[0xF00A, 0xF029, 0xDCD5, 0x7C00 | CELL_WIDTH as u16]);
+    let wait_release = rom.here();
+    rom.ops(&[0xE0A1, 0x1000 | wait_release, 0x1000 | wait_key]);
+
+    rom.data.clone()
+}
@@ -0,0 +1,147 @@
+//! Property tests running random programs, random numbers and key presses in the Chip8 VM and
+//! in an independent reference model, checking that the VM never panics and that both reach
+//! the same state after every instruction.
+//!
+//! The same checker is used by the `tick` cargo fuzz target in the `fuzz` folder.
+
+mod model;
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+use chip8vm::quirks::{IndexRegister, MemoryPolicy, Quirks, SpriteMode};
+use chip8vm::PROGRAM_SIZE;
+
+/// Maximum number of instructions executed by a program.
+const TICKS: usize = 2000;
+
+/// Returns a strategy generating valid instructions. Jumps and calls are less frequent and
+/// target even addresses of the program, values are biased towards the limits of a byte and I
+/// addresses towards the program and the end of the memory.
+fn instruction() -> impl Strategy<Value = u16> {
+    let target = (0x100u16..0x180).prop_map(|word| word * 2);
+    let address = prop_oneof![0x200u16..0x300, 0xF00u16..0x1000, 0u16..0x1000];
+    let register = 0u16..16;
+    let byte = prop_oneof![any::<u8>(), prop::sample::select(vec![0x00u8, 0x01, 0x7F, 0x80, 0xFF])].prop_map(u16::from);
+
+    prop_oneof![
+        1 => Just(0x00E0u16),
+        1 => Just(0x00EE),
+        1 => target.clone().prop_map(|address| 0x1000 | address),
+        1 => target.clone().prop_map(|address| 0x2000 | address),
+        1 => target.prop_map(|address| 0xB000 | (address - 0x80)),
+        8 => (0x3u16..=0x7, register.clone(), byte).prop_map(|(op, x, nn)| op << 12 | x << 8 | nn),
+        8 => (register.clone(), register.clone(), prop::sample::select(vec![0x0u16, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0xE]))
+            .prop_map(|(x, y, n)| 0x8000 | x << 8 | y << 4 | n),
+        2 => (prop::sample::select(vec![0x5u16, 0x9]), register.clone(), register.clone())
+            .prop_map(|(op, x, y)| op << 12 | x << 8 | y << 4),
+        3 => address.prop_map(|address| 0xA000 | address),
+        2 => (register.clone(), any::<u8>()).prop_map(|(x, nn)| 0xC000 | x << 8 | nn as u16),
+        3 => (register.clone(), register.clone(), 0u16..16).prop_map(|(x, y, n)| 0xD000 | x << 8 | y << 4 | n),
+        2 => (register.clone(), prop::sample::select(vec![0x9Eu16, 0xA1])).prop_map(|(x, nn)| 0xE000 | x << 8 | nn),
+        6 => (
+            register,
+            prop::sample::select(vec![0x07u16, 0x0A, 0x15, 0x18, 0x1E, 0x29, 0x33, 0x55, 0x65])
+        )
+            .prop_map(|(x, nn)| 0xF000 | x << 8 | nn),
+    ]
+}
+
+/// Returns a strategy generating programs of valid instructions ending with a jump to the
+/// start, so they run until they use the stack wrongly.
+fn program() -> impl Strategy<Value = Vec<u8>> {
+    vec(instruction(), 32..128).prop_map(|instructions| {
+        instructions
+            .iter()
+            .chain([0x1200].iter())
+            .flat_map(|opcode| opcode.to_be_bytes())
+            .collect()
+    })
+}
+
+fn keys() -> impl Strategy<Value = Vec<Option<u8>>> {
+    vec(option::of(0u8..16), 1..8)
+}
+
+const POLICIES: [MemoryPolicy; 3] = [MemoryPolicy::Wrap, MemoryPolicy::Clamp, MemoryPolicy::Fault];
+const INDEX_REGISTERS: [IndexRegister; 3] = [IndexRegister::Wrap12, IndexRegister::Overflow, IndexRegister::Bits16];
+const SPRITE_MODES: [SpriteMode; 3] = [SpriteMode::Wrap, SpriteMode::Clip, SpriteMode::SchipRows];
+
+/// Returns the quirks supported by the model. Original selects the VF reset, shift and load
+/// and store quirks of the COSMAC VIP.
+fn quirks(memory: MemoryPolicy, index: IndexRegister, sprite: SpriteMode, display_wait: bool, original: bool) -> Quirks {
+    Quirks {
+        memory,
+        index,
+        sprite,
+        display_wait,
+        vf_reset: original,
+        shift_vy: original,
+        load_store_increment: original,
+        ..Quirks::CHIP8
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    #[test]
+    fn valid_instructions(
+        rom in program(),
+        memory in prop::sample::select(&POLICIES[..]),
+        index in prop::sample::select(&INDEX_REGISTERS[..]),
+        sprite in prop::sample::select(&SPRITE_MODES[..]),
+        display_wait in any::<bool>(),
+        original in any::<bool>(),
+        randoms in vec(any::<u8>(), 1..16),
+        keys in keys(),
+    ) {
+        model::check(&rom, quirks(memory, index, sprite, display_wait, original), &randoms, &keys, TICKS);
+    }
+
+    #[test]
+    fn random_bytes(
+        rom in vec(any::<u8>(), 0..PROGRAM_SIZE),
+        memory in prop::sample::select(&POLICIES[..]),
+        index in prop::sample::select(&INDEX_REGISTERS[..]),
+        sprite in prop::sample::select(&SPRITE_MODES[..]),
+        display_wait in any::<bool>(),
+        original in any::<bool>(),
+        randoms in vec(any::<u8>(), 1..16),
+        keys in keys(),
+    ) {
+        model::check(&rom, quirks(memory, index, sprite, display_wait, original), &randoms, &keys, TICKS);
+    }
+}
+
+#[test]
+fn stack_limits() {
+    // RET with an empty stack.
+    model::check(&[0x00, 0xEE], Quirks::CHIP8, &[], &[], TICKS);
+    // Recursive CALL filling the stack.
+    model::check(&[0x22, 0x00], Quirks::CHIP8, &[], &[], TICKS);
+}
+
+#[test]
+fn memory_end() {
+    // Stores, loads and draws crossing the end of the memory, and execution from 0xFFE.
+    let rom = [0xAF, 0xFE, 0x60, 0xFF, 0xF0, 0x33, 0xF5, 0x55, 0xAF, 0xFC, 0xFF, 0x65, 0xDF, 0xFF, 0xFF, 0x29, 0x1F, 0xFE];
+    for memory in POLICIES.iter() {
+        for index in INDEX_REGISTERS.iter() {
+            model::check(&rom, quirks(*memory, *index, SpriteMode::Wrap, false, true), &[], &[], TICKS);
+        }
+    }
+}
+
+#[test]
+fn screen_edges() {
+    // Sprites crossing the right and bottom edges and starting outside the screen, drawn twice.
+    let rom = [0xA2, 0x00, 0x60, 0x3C, 0x61, 0x1D, 0xD0, 0x1F, 0xD0, 0x1F, 0x60, 0x7E, 0x61, 0xFE, 0xD0, 0x18, 0xD0, 0x18, 0x12, 0x00];
+    for sprite in SPRITE_MODES.iter() {
+        for display_wait in [false, true].iter() {
+            model::check(&rom, quirks(MemoryPolicy::Wrap, IndexRegister::Bits16, *sprite, *display_wait, true), &[], &[], TICKS);
+        }
+    }
+}
+
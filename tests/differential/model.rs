@@ -0,0 +1,354 @@
+//! Reference model of the Chip8 instructions, written from the instruction descriptions
+//! independently of chip::Chip, and a checker running both side by side.
+//!
+//! The model follows the default Quirks::CHIP8 behaviour of the Chip8 VM, with any memory
+//! policy, I register width, sprite mode and display wait: shifts use VX and FX55 and FX65
+//! increment I.
+
+use chip8vm::chip::Chip;
+use chip8vm::font::Font;
+use chip8vm::framebuffer::FrameBuffer;
+use chip8vm::quirks::{IndexRegister, MemoryPolicy, Quirks, SpriteMode};
+use chip8vm::{Keypad, Random, MEMORY_SIZE, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+const START: usize = 0x200;
+const STACK_DEPTH: usize = 16;
+
+/// Instructions executed between two timer ticks.
+const TICKS_X_FRAME: usize = 10;
+
+/// Instructions executed with the same key pressed.
+const TICKS_X_KEY: usize = 50;
+
+/// Reference model state.
+pub struct Model {
+    pub v: [u8; 16],
+    pub i: u16,
+    pub pc: usize,
+    pub stack: Vec<u16>,
+    pub delay: u8,
+    pub sound: u8,
+    pub memory: Vec<u8>,
+    pub pixels: [[bool; SCREEN_WIDTH]; SCREEN_HEIGHT],
+    pub policy: MemoryPolicy,
+    pub index: IndexRegister,
+    pub sprite: SpriteMode,
+    pub display_wait: bool,
+    pub vf_reset: bool,
+    pub shift_vy: bool,
+    pub load_store_increment: bool,
+    pub drawn: bool,
+    pub faulted: bool,
+}
+
+impl Model {
+    /// Creates the model with the font at 0x000 and the ROM at 0x200.
+    pub fn new(rom: &[u8], quirks: &Quirks) -> Model {
+        let mut memory = vec![0; MEMORY_SIZE];
+        memory[..Font::CHIP8.set.len()].copy_from_slice(&Font::CHIP8.set);
+        memory[START..START + rom.len()].copy_from_slice(rom);
+
+        Model {
+            v: [0; 16],
+            i: 0,
+            pc: START,
+            stack: Vec::new(),
+            delay: 0,
+            sound: 0,
+            memory,
+            pixels: [[false; SCREEN_WIDTH]; SCREEN_HEIGHT],
+            policy: quirks.memory,
+            index: quirks.index,
+            sprite: quirks.sprite,
+            display_wait: quirks.display_wait,
+            vf_reset: quirks.vf_reset,
+            shift_vy: quirks.shift_vy,
+            load_store_increment: quirks.load_store_increment,
+            drawn: false,
+            faulted: false,
+        }
+    }
+
+    /// Applies the memory policy. Faulting accesses are ignored and stop the execution after
+    /// the instruction.
+    fn locate(&mut self, address: usize) -> Option<usize> {
+        match self.policy {
+            _ if address < MEMORY_SIZE => Some(address),
+            MemoryPolicy::Wrap => Some(address & 0xFFF),
+            MemoryPolicy::Clamp => Some(0xFFF),
+            MemoryPolicy::Fault => {
+                self.faulted = true;
+                None
+            }
+        }
+    }
+
+    /// Sets I, truncated to its width.
+    fn set_i(&mut self, value: usize) {
+        self.i = match self.index {
+            IndexRegister::Wrap12 => value as u16 & 0xFFF,
+            _ => value as u16,
+        };
+    }
+
+    fn load(&mut self, address: usize) -> u8 {
+        self.locate(address).map_or(0, |address| self.memory[address])
+    }
+
+    fn store(&mut self, address: usize, value: u8) {
+        if let Some(address) = self.locate(address) {
+            self.memory[address] = value;
+        }
+    }
+
+    /// Decreases the timers and starts a new display frame, like a 60Hz tick.
+    pub fn frame(&mut self) {
+        self.drawn = false;
+        self.delay = self.delay.saturating_sub(1);
+        self.sound = self.sound.saturating_sub(1);
+    }
+
+    /// Executes one instruction, returning false if the instruction can not be executed.
+    pub fn step(&mut self, random: &mut impl Random, key: Option<u8>) -> bool {
+        self.faulted = false;
+        match self.locate(self.pc) {
+            Some(pc) => self.pc = pc,
+            None => return false,
+        }
+        let opcode = u16::from_be_bytes([self.load(self.pc), self.load(self.pc + 1)]);
+        if self.faulted {
+            return false;
+        }
+        self.pc += 2;
+
+        let x = (opcode >> 8 & 0xF) as usize;
+        let y = (opcode >> 4 & 0xF) as usize;
+        let n = opcode & 0xF;
+        let nn = opcode as u8;
+        let nnn = opcode & 0xFFF;
+
+        match opcode >> 12 {
+            0x0 if opcode == 0x00E0 => self.pixels = [[false; SCREEN_WIDTH]; SCREEN_HEIGHT],
+            0x0 if opcode == 0x00EE => match self.stack.pop() {
+                Some(address) => self.pc = address as usize,
+                None => return false,
+            },
+            0x0 => return false,
+            0x1 => self.pc = nnn as usize,
+            0x2 => {
+                if self.stack.len() == STACK_DEPTH {
+                    return false;
+                }
+                self.stack.push(self.pc as u16);
+                self.pc = nnn as usize;
+            }
+            0x3 => self.skip_if(self.v[x] == nn),
+            0x4 => self.skip_if(self.v[x] != nn),
+            0x5 => self.skip_if(self.v[x] == self.v[y]),
+            0x6 => self.v[x] = nn,
+            0x7 => self.v[x] = self.v[x].wrapping_add(nn),
+            0x8 => {
+                let reset = if self.vf_reset { Some(0) } else { None };
+                let shifted = if self.shift_vy { self.v[y] } else { self.v[x] };
+                let (result, flag) = match n {
+                    0x0 => (self.v[y], None),
+                    0x1 => (self.v[x] | self.v[y], reset),
+                    0x2 => (self.v[x] & self.v[y], reset),
+                    0x3 => (self.v[x] ^ self.v[y], reset),
+                    0x4 => {
+                        let sum = self.v[x] as u16 + self.v[y] as u16;
+                        (sum as u8, Some((sum > 0xFF) as u8))
+                    }
+                    0x5 => (self.v[x].wrapping_sub(self.v[y]), Some((self.v[x] >= self.v[y]) as u8)),
+                    0x6 => (shifted >> 1, Some(shifted & 1)),
+                    0x7 => (self.v[y].wrapping_sub(self.v[x]), Some((self.v[y] >= self.v[x]) as u8)),
+                    0xE => (shifted << 1, Some(shifted >> 7)),
+                    _ => return false,
+                };
+                self.v[x] = result;
+                if let Some(flag) = flag {
+                    self.v[0xF] = flag;
+                }
+            }
+            0x9 => self.skip_if(self.v[x] != self.v[y]),
+            0xA => self.i = nnn,
+            0xB => self.pc = nnn as usize + self.v[0] as usize,
+            0xC => self.v[x] = random.range() & nn,
+            0xD => self.draw(self.v[x] as usize, self.v[y] as usize, n as usize),
+            0xE if nn == 0x9E => self.skip_if(key == Some(self.v[x])),
+            0xE if nn == 0xA1 => self.skip_if(key != Some(self.v[x])),
+            0xF => match nn {
+                0x07 => self.v[x] = self.delay,
+                0x0A => match key {
+                    Some(key) => self.v[x] = key,
+                    None => self.pc -= 2,
+                },
+                0x15 => self.delay = self.v[x],
+                0x18 => self.sound = self.v[x],
+                0x1E => {
+                    let sum = self.i as usize + self.v[x] as usize;
+                    self.set_i(sum);
+                    if self.index == IndexRegister::Overflow {
+                        self.v[0xF] = (sum > 0xFFF) as u8;
+                    }
+                }
+                0x29 => self.set_i(self.v[x] as usize * 5),
+                0x33 => {
+                    let i = self.i as usize;
+                    self.store(i, self.v[x] / 100);
+                    self.store(i + 1, self.v[x] / 10 % 10);
+                    self.store(i + 2, self.v[x] % 10);
+                }
+                0x55 => {
+                    for register in 0..=x {
+                        self.store(self.i as usize + register, self.v[register]);
+                    }
+                    if self.load_store_increment {
+                        self.set_i(self.i as usize + x + 1);
+                    }
+                }
+                0x65 => {
+                    for register in 0..=x {
+                        self.v[register] = self.load(self.i as usize + register);
+                    }
+                    if self.load_store_increment {
+                        self.set_i(self.i as usize + x + 1);
+                    }
+                }
+                _ => return false,
+            },
+            _ => return false,
+        }
+
+        !self.faulted
+    }
+
+    fn skip_if(&mut self, condition: bool) {
+        if condition {
+            self.pc += 2;
+        }
+    }
+
+    /// Draws a sprite, or waits for the next frame if one was already drawn with display wait.
+    fn draw(&mut self, x: usize, y: usize, rows: usize) {
+        if self.display_wait && self.drawn {
+            self.pc -= 2;
+            return;
+        }
+        self.drawn = true;
+
+        let wrap = self.sprite == SpriteMode::Wrap;
+        let (x, y) = if wrap { (x, y) } else { (x % SCREEN_WIDTH, y % SCREEN_HEIGHT) };
+        let mut collided_rows = 0;
+        for row in 0..rows {
+            if self.sprite == SpriteMode::SchipRows && y + row >= SCREEN_HEIGHT {
+                collided_rows += 1;
+                continue;
+            }
+            let data = self.load(self.i as usize + row);
+            let mut collision = false;
+            for column in 0..8 {
+                let (pixel_x, pixel_y) = (x + column, y + row);
+                if data & (0x80 >> column) == 0 || (!wrap && (pixel_x >= SCREEN_WIDTH || pixel_y >= SCREEN_HEIGHT)) {
+                    continue;
+                }
+                let pixel = &mut self.pixels[pixel_y % SCREEN_HEIGHT][pixel_x % SCREEN_WIDTH];
+                collision |= *pixel;
+                *pixel = !*pixel;
+            }
+            collided_rows += collision as u8;
+        }
+        self.v[0xF] = match self.sprite {
+            SpriteMode::SchipRows => collided_rows,
+            SpriteMode::Wrap | SpriteMode::Clip => (collided_rows > 0) as u8,
+        };
+    }
+}
+
+/// Random implementation returning the values of a list, in a loop.
+#[derive(Clone)]
+pub struct ScriptedRandom {
+    values: Vec<u8>,
+    position: usize,
+}
+
+impl ScriptedRandom {
+    pub fn new(values: &[u8]) -> ScriptedRandom {
+        ScriptedRandom {
+            values: if values.is_empty() { vec![0] } else { values.to_vec() },
+            position: 0,
+        }
+    }
+}
+
+impl Random for ScriptedRandom {
+    fn range(&mut self) -> u8 {
+        let value = self.values[self.position % self.values.len()];
+        self.position += 1;
+        value
+    }
+}
+
+struct ScriptedKeypad {
+    key: Option<u8>,
+}
+
+impl Keypad for ScriptedKeypad {
+    fn is_pressed(&self, keycode: u8) -> bool {
+        self.key == Some(keycode)
+    }
+
+    fn pressed_key(&self) -> Option<u8> {
+        self.key
+    }
+}
+
+/// Runs the ROM in the Chip8 VM and in the model, panicking at the first difference.
+///
+/// # Parameters
+/// * rom - Program, up to the program size.
+/// * quirks - Memory policy, I register width, sprite mode and display wait of the VM and the
+///   model.
+/// * randoms - Values returned by the random generator, in a loop.
+/// * keys - Keys pressed during TICKS_X_KEY instructions each, in a loop.
+/// * ticks - Maximum number of instructions to execute.
+pub fn check(rom: &[u8], quirks: Quirks, randoms: &[u8], keys: &[Option<u8>], ticks: usize) {
+    let mut chip = Chip::default();
+    chip.set_quirks(quirks);
+    let mut chip_random = ScriptedRandom::new(randoms);
+    let mut screen = FrameBuffer::default();
+    let mut keypad = ScriptedKeypad { key: None };
+    assert!(chip.load_rom(rom));
+
+    let mut model = Model::new(rom, &quirks);
+    let mut model_random = ScriptedRandom::new(randoms);
+
+    for tick in 0..ticks {
+        if !keys.is_empty() {
+            keypad.key = keys[tick / TICKS_X_KEY % keys.len()];
+        }
+
+        let executed = chip.tick(&mut chip_random, &mut screen, &keypad);
+        assert_eq!(executed, model.step(&mut model_random, keypad.key), "Tick {}: execution", tick);
+        if !executed {
+            break;
+        }
+        if tick % TICKS_X_FRAME == TICKS_X_FRAME - 1 {
+            chip.tick_timers();
+            model.frame();
+        }
+
+        assert_eq!(chip.registers(), &model.v, "Tick {}: registers", tick);
+        assert_eq!(chip.i(), model.i, "Tick {}: I", tick);
+        assert_eq!(chip.ip(), model.pc, "Tick {}: instruction pointer", tick);
+        assert_eq!(chip.stack(), &model.stack[..], "Tick {}: stack", tick);
+        assert_eq!(chip.delay_timer(), model.delay, "Tick {}: delay timer", tick);
+        assert_eq!(chip.sound_timer(), model.sound, "Tick {}: sound timer", tick);
+        assert!(chip.memory() == &model.memory[..], "Tick {}: memory", tick);
+        for (y, row) in model.pixels.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                assert_eq!(screen.is_on(x, y), *pixel, "Tick {}: pixel ({}, {})", tick, x, y);
+            }
+        }
+    }
+}